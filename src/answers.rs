@@ -0,0 +1,182 @@
+use crate::error::{bail, parse, require_with, try_with, Error, Result};
+use crate::Answer;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::str::FromStr;
+
+// Known-correct answers keyed by (day, part). Stored one per line as `day part answer`, with
+// backslashes and newlines in the answer escaped so that multi-line answers (day 10) fit on a line.
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers(BTreeMap<(u8, u8), String>);
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Match,
+    Mismatch { expected: String, actual: String },
+    Missing { actual: String },
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => s.parse(),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(Answers::default()),
+            Err(err) => bail!("failed to read {}: {}", path.display(), err),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        try_with!(
+            fs::write(path, self.to_string()),
+            "failed to write {}",
+            path.display()
+        );
+        Ok(())
+    }
+
+    pub fn record(&mut self, day: u8, answer: &Answer) {
+        self.0.insert((day, 1), answer.part1.clone());
+        self.0.insert((day, 2), answer.part2.clone());
+    }
+
+    pub fn verify(&self, day: u8, answer: &Answer) -> [Verdict; 2] {
+        [
+            self.check(day, 1, &answer.part1),
+            self.check(day, 2, &answer.part2),
+        ]
+    }
+
+    fn check(&self, day: u8, part: u8, actual: &str) -> Verdict {
+        match self.0.get(&(day, part)) {
+            Some(expected) if expected == actual => Verdict::Match,
+            Some(expected) => Verdict::Mismatch {
+                expected: expected.clone(),
+                actual: actual.to_string(),
+            },
+            None => Verdict::Missing {
+                actual: actual.to_string(),
+            },
+        }
+    }
+}
+
+impl FromStr for Answers {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut answers = BTreeMap::new();
+
+        for l in s.lines().filter(|l| !l.trim().is_empty()) {
+            let mut fields = l.splitn(3, ' ');
+            let day = parse(require_with!(fields.next(), "missing day in {}", l))?;
+            let part = parse(require_with!(fields.next(), "missing part in {}", l))?;
+            let answer = require_with!(fields.next(), "missing answer in {}", l);
+
+            if part != 1 && part != 2 {
+                bail!("part must be 1 or 2 in {}", l);
+            }
+
+            answers.insert((day, part), unescape(answer)?);
+        }
+
+        Ok(Answers(answers))
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ((day, part), answer) in self.0.iter() {
+            writeln!(f, "{} {} {}", day, part, escape(answer))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Match => write!(f, "ok"),
+            Verdict::Mismatch { expected, actual } => write!(
+                f,
+                "MISMATCH expected {} but got {}",
+                escape(expected),
+                escape(actual)
+            ),
+            Verdict::Missing { actual } => write!(f, "missing (got {})", escape(actual)),
+        }
+    }
+}
+
+pub fn escape(s: &str) -> String {
+    s.replace('\\', r"\\").replace('\n', r"\n")
+}
+
+pub fn unescape(s: &str) -> Result<String> {
+    let mut res = String::new();
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('\\') => res.push('\\'),
+                Some('n') => res.push('\n'),
+                _ => bail!("bad escape in answer {}", s),
+            }
+        } else {
+            res.push(c);
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut answers = Answers::default();
+        answers.record(1, &Answer::new(470, 790));
+        answers.record(10, &Answer::new("\n#..#\n.\\.\n", 10009));
+
+        assert_eq!(answers.to_string().parse(), Ok(answers));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("1 3 470".parse::<Answers>().is_err());
+        assert!("1 1".parse::<Answers>().is_err());
+        assert!("1 1 a\\b".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn verify() {
+        let answers: Answers = "1 1 470\n1 2 791\n".parse().unwrap();
+
+        assert_eq!(
+            answers.verify(1, &Answer::new(470, 790)),
+            [
+                Verdict::Match,
+                Verdict::Mismatch {
+                    expected: "791".to_string(),
+                    actual: "790".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            answers.verify(2, &Answer::new(6175, "x")),
+            [
+                Verdict::Missing {
+                    actual: "6175".to_string(),
+                },
+                Verdict::Missing {
+                    actual: "x".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,294 @@
+// A circular doubly linked list stored in a Vec, with links held as indices rather than pointers.
+// Removed slots are reused by later insertions, so the Vec only grows to the peak length.
+#[derive(Debug, Clone)]
+pub struct Circle<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+    current: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: Option<T>,
+    left: usize,
+    right: usize,
+}
+
+impl<T> Circle<T> {
+    pub fn new() -> Self {
+        Circle {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            current: 0,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Circle {
+            nodes: Vec::with_capacity(capacity),
+            ..Circle::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.nodes[self.current].value.as_ref()
+        }
+    }
+
+    // Inserts a value immediately clockwise of the current one, and makes it current.
+    pub fn insert(&mut self, value: T) {
+        let (left, right) = if self.is_empty() {
+            (None, None)
+        } else {
+            (Some(self.current), Some(self.nodes[self.current].right))
+        };
+
+        let node = Node {
+            value: Some(value),
+            left: 0,
+            right: 0,
+        };
+        let i = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        let left = left.unwrap_or(i);
+        let right = right.unwrap_or(i);
+        self.nodes[i].left = left;
+        self.nodes[i].right = right;
+        self.nodes[left].right = i;
+        self.nodes[right].left = i;
+
+        self.current = i;
+        self.len += 1;
+    }
+
+    // Removes the current value, making the one clockwise of it current.
+    pub fn remove(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let i = self.current;
+        let Node { left, right, .. } = self.nodes[i];
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+
+        self.free.push(i);
+        self.current = right;
+        self.len -= 1;
+
+        self.nodes[i].value.take()
+    }
+
+    // Moves the current position n steps clockwise, or anticlockwise if n is negative.
+    pub fn rotate(&mut self, n: isize) {
+        if self.is_empty() {
+            return;
+        }
+
+        let len = self.len as isize;
+        let n = n.rem_euclid(len);
+
+        if n <= len / 2 {
+            for _ in 0..n {
+                self.current = self.nodes[self.current].right;
+            }
+        } else {
+            for _ in n..len {
+                self.current = self.nodes[self.current].left;
+            }
+        }
+    }
+
+    // The values in clockwise order, starting with the current one.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            circle: self,
+            next: self.current,
+            remaining: self.len,
+            clockwise: true,
+        }
+    }
+
+    // The values in anticlockwise order, starting with the current one.
+    pub fn iter_rev(&self) -> Iter<'_, T> {
+        Iter {
+            clockwise: false,
+            ..self.iter()
+        }
+    }
+}
+
+impl<T> Default for Circle<T> {
+    fn default() -> Self {
+        Circle::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    circle: &'a Circle<T>,
+    next: usize,
+    remaining: usize,
+    clockwise: bool,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = &self.circle.nodes[self.next];
+        self.next = if self.clockwise {
+            node.right
+        } else {
+            node.left
+        };
+        self.remaining -= 1;
+        node.value.as_ref()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Circle<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn values(circle: &Circle<i32>) -> Vec<i32> {
+        circle.iter().cloned().collect()
+    }
+
+    #[test]
+    fn insert_and_rotate() {
+        let mut circle = Circle::new();
+        for i in 0..5 {
+            circle.insert(i);
+        }
+
+        assert_eq!(circle.len(), 5);
+        assert_eq!(values(&circle), vec![4, 0, 1, 2, 3]);
+
+        circle.rotate(2);
+        assert_eq!(circle.current(), Some(&1));
+        circle.rotate(-3);
+        assert_eq!(circle.current(), Some(&3));
+        circle.rotate(-11);
+        assert_eq!(circle.current(), Some(&2));
+        circle.rotate(10);
+        assert_eq!(circle.current(), Some(&2));
+    }
+
+    #[test]
+    fn remove() {
+        let mut circle = Circle::new();
+        assert_eq!(circle.remove(), None);
+
+        for i in 0..4 {
+            circle.insert(i);
+        }
+        circle.rotate(-2);
+
+        assert_eq!(circle.remove(), Some(1));
+        assert_eq!(values(&circle), vec![2, 3, 0]);
+
+        // Freed slots are reused.
+        circle.insert(7);
+        assert_eq!(values(&circle), vec![7, 3, 0, 2]);
+        assert_eq!(circle.nodes.len(), 4);
+
+        for _ in 0..4 {
+            circle.remove();
+        }
+        assert!(circle.is_empty());
+        assert_eq!(circle.current(), None);
+        assert_eq!(values(&circle), vec![]);
+
+        circle.insert(9);
+        assert_eq!(values(&circle), vec![9]);
+    }
+
+    #[test]
+    fn iter_rev() {
+        let mut circle = Circle::new();
+        assert_eq!(circle.iter_rev().next(), None);
+
+        for i in 0..5 {
+            circle.insert(i);
+        }
+        circle.rotate(2);
+
+        // One lap each way, starting from the current value.
+        assert_eq!(
+            circle.iter_rev().cloned().collect::<Vec<_>>(),
+            vec![1, 0, 4, 3, 2]
+        );
+        assert_eq!(values(&circle), vec![1, 2, 3, 4, 0]);
+        assert_eq!(circle.iter_rev().size_hint(), (5, Some(5)));
+    }
+
+    // Counts how many times values are dropped.
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn values_are_dropped_once() {
+        let drops = Rc::new(Cell::new(0));
+        let mut circle = Circle::new();
+        for _ in 0..10 {
+            circle.insert(Counted(drops.clone()));
+        }
+
+        circle.rotate(3);
+        for _ in 0..4 {
+            drop(circle.remove());
+        }
+        assert_eq!(drops.get(), 4);
+
+        circle.insert(Counted(drops.clone()));
+        drop(circle);
+        assert_eq!(drops.get(), 11);
+        assert_eq!(Rc::strong_count(&drops), 1);
+    }
+}
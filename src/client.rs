@@ -0,0 +1,491 @@
+use crate::answers::{escape, unescape};
+use crate::error::{bail, parse, re, require_with, try_with, Error, Result};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+// A client for an Advent of Code-compatible server. Requests are made by shelling out to curl, so
+// that https works without pulling a TLS stack into the build.
+pub struct Client {
+    base_url: String,
+    session: Option<String>,
+    cache_dir: PathBuf,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: Option<String>, cache_dir: PathBuf) -> Self {
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+            cache_dir,
+        }
+    }
+
+    // Configured by AOC_BASE_URL, AOC_SESSION and AOC_CACHE.
+    pub fn from_env() -> Self {
+        Client::new(
+            &env::var("AOC_BASE_URL").unwrap_or_else(|_| "https://adventofcode.com".to_string()),
+            env::var("AOC_SESSION").ok(),
+            env::var_os("AOC_CACHE")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("inputs")),
+        )
+    }
+
+    // The input for the given day, downloaded once and read from the cache thereafter.
+    pub fn input(&self, day: u8) -> Result<String> {
+        let path = self.cache_dir.join(format!("day{:02}", day));
+
+        match fs::read_to_string(&path) {
+            Ok(input) => return Ok(input),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => bail!("failed to read {}: {}", path.display(), err),
+        }
+
+        let input = self.get(&format!("/2018/day/{}/input", day))?;
+
+        try_with!(
+            fs::create_dir_all(&self.cache_dir),
+            "failed to create {}",
+            self.cache_dir.display()
+        );
+        try_with!(
+            fs::write(&path, &input),
+            "failed to write {}",
+            path.display()
+        );
+
+        Ok(input)
+    }
+
+    // Submits an answer and parses the verdict. Answers which are already known to be wrong (or
+    // which fall outside the bounds of previous "too high" and "too low" verdicts) are refused
+    // without contacting the server, as is anything for a part that has already been solved.
+    pub fn submit(&self, day: u8, part: u8, answer: &str) -> Result<Outcome> {
+        let path = self.cache_dir.join("submissions");
+        let mut submissions = Submissions::load(&path)?;
+
+        if let Some(reason) = submissions.refuse(day, part, answer) {
+            bail!(
+                "not submitting {} for day {} part {}: {}",
+                answer,
+                day,
+                part,
+                reason
+            );
+        }
+
+        let outcome = parse_outcome(&self.post(
+            &format!("/2018/day/{}/answer", day),
+            &format!("level={}", part),
+            &format!("answer={}", answer),
+        )?)?;
+
+        if outcome.is_final() {
+            submissions
+                .0
+                .push((day, part, outcome.clone(), answer.to_string()));
+            try_with!(
+                fs::create_dir_all(&self.cache_dir),
+                "failed to create {}",
+                self.cache_dir.display()
+            );
+            submissions.save(&path)?;
+        }
+
+        Ok(outcome)
+    }
+
+    fn get(&self, path: &str) -> Result<String> {
+        self.curl(path, &[])
+    }
+
+    fn post(&self, path: &str, level: &str, answer: &str) -> Result<String> {
+        self.curl(path, &["--data", level, "--data-urlencode", answer])
+    }
+
+    fn curl(&self, path: &str, args: &[&str]) -> Result<String> {
+        let session = require_with!(self.session.as_ref(), "AOC_SESSION is not set");
+        let url = format!("{}{}", self.base_url, path);
+
+        // The session cookie is passed as config on stdin rather than as an argument, where any
+        // other user could read it from the process list.
+        let mut child = try_with!(
+            Command::new("curl")
+                .args(["--silent", "--show-error", "--fail", "--config", "-"])
+                .args(args)
+                .arg(&url)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn(),
+            "failed to run curl"
+        );
+        let config = format!(
+            "cookie = \"session={}\"\n",
+            session.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        if let Some(mut stdin) = child.stdin.take() {
+            try_with!(
+                stdin.write_all(config.as_bytes()),
+                "failed to pass the session to curl"
+            );
+        }
+        let output = try_with!(child.wait_with_output(), "failed to run curl");
+
+        if !output.status.success() {
+            bail!(
+                "request to {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(try_with!(
+            String::from_utf8(output.stdout),
+            "response from {} was not utf-8",
+            url
+        ))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    Incorrect,
+    // Submitted too recently, with the time left to wait if the server said.
+    Wait(Option<String>),
+    // The part has already been solved, or its prerequisite hasn't been.
+    WrongLevel,
+}
+
+impl Outcome {
+    // Whether resubmitting the same answer could give a different verdict.
+    fn is_final(&self) -> bool {
+        match self {
+            Outcome::Correct | Outcome::TooHigh | Outcome::TooLow | Outcome::Incorrect => true,
+            Outcome::Wait(_) | Outcome::WrongLevel => false,
+        }
+    }
+
+    fn key(&self) -> &str {
+        match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "high",
+            Outcome::TooLow => "low",
+            Outcome::Incorrect => "wrong",
+            Outcome::Wait(_) => "wait",
+            Outcome::WrongLevel => "level",
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "that's the right answer"),
+            Outcome::TooHigh => write!(f, "too high"),
+            Outcome::TooLow => write!(f, "too low"),
+            Outcome::Incorrect => write!(f, "that's not the right answer"),
+            Outcome::Wait(Some(left)) => write!(f, "please wait ({} left)", left),
+            Outcome::Wait(None) => write!(f, "please wait"),
+            Outcome::WrongLevel => write!(f, "not solving the right level"),
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "correct" => Ok(Outcome::Correct),
+            "high" => Ok(Outcome::TooHigh),
+            "low" => Ok(Outcome::TooLow),
+            "wrong" => Ok(Outcome::Incorrect),
+            _ => bail!("unknown outcome {}", s),
+        }
+    }
+}
+
+fn parse_outcome(body: &str) -> Result<Outcome> {
+    if body.contains("That's the right answer") {
+        Ok(Outcome::Correct)
+    } else if body.contains("too high") {
+        Ok(Outcome::TooHigh)
+    } else if body.contains("too low") {
+        Ok(Outcome::TooLow)
+    } else if body.contains("That's not the right answer") {
+        Ok(Outcome::Incorrect)
+    } else if body.contains("too recently") || body.contains("please wait") {
+        Ok(Outcome::Wait(
+            re(r"You have (.+?) left to wait")?
+                .captures(body)
+                .map(|caps| caps[1].to_string()),
+        ))
+    } else if body.contains("You don't seem to be solving the right level") {
+        Ok(Outcome::WrongLevel)
+    } else {
+        bail!("couldn't parse response {}", body.trim())
+    }
+}
+
+// Every final verdict we've received, stored one per line as `day part outcome answer`.
+#[derive(Debug, Default, PartialEq)]
+struct Submissions(Vec<(u8, u8, Outcome, String)>);
+
+impl Submissions {
+    fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(s) => s.parse(),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(Submissions::default()),
+            Err(err) => bail!("failed to read {}: {}", path.display(), err),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        try_with!(
+            fs::write(path, self.to_string()),
+            "failed to write {}",
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn refuse(&self, day: u8, part: u8, answer: &str) -> Option<String> {
+        let n = answer.parse::<i64>().ok();
+
+        for (_, _, outcome, previous) in
+            self.0.iter().filter(|(d, p, _, _)| *d == day && *p == part)
+        {
+            if *outcome == Outcome::Correct {
+                return Some(format!("already solved with {}", previous));
+            }
+            if previous == answer {
+                return Some(format!("already submitted ({})", outcome));
+            }
+            if let (Some(n), Ok(m)) = (n, previous.parse::<i64>()) {
+                if *outcome == Outcome::TooHigh && n >= m {
+                    return Some(format!("{} was too high", m));
+                }
+                if *outcome == Outcome::TooLow && n <= m {
+                    return Some(format!("{} was too low", m));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl FromStr for Submissions {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                let mut fields = l.splitn(4, ' ');
+                Ok((
+                    parse(require_with!(fields.next(), "missing day in {}", l))?,
+                    parse(require_with!(fields.next(), "missing part in {}", l))?,
+                    parse(require_with!(fields.next(), "missing outcome in {}", l))?,
+                    unescape(require_with!(fields.next(), "missing answer in {}", l))?,
+                ))
+            })
+            .collect::<Result<_>>()
+            .map(Submissions)
+    }
+}
+
+impl fmt::Display for Submissions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (day, part, outcome, answer) in self.0.iter() {
+            writeln!(f, "{} {} {} {}", day, part, outcome.key(), escape(answer))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    // A stand-in server that answers a single request with the given body, and hands back the
+    // request line, cookie header and request body it received.
+    fn serve(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, JoinHandle<(String, String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut cookie = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                let header = header.trim();
+                if header.is_empty() {
+                    break;
+                }
+                let (name, value) = header.split_at(header.find(':').unwrap());
+                let value = value[1..].trim();
+                match name.to_lowercase().as_str() {
+                    "cookie" => cookie = value.to_string(),
+                    "content-length" => content_length = value.parse().unwrap(),
+                    _ => {}
+                }
+            }
+
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+
+            (
+                request_line.trim().to_string(),
+                cookie,
+                String::from_utf8(request_body).unwrap(),
+            )
+        });
+
+        (url, handle)
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-2018-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn fetches_and_caches_input() {
+        let (url, server) = serve("200 OK", "+1\n-2\n");
+        let cache_dir = cache_dir("fetch");
+        let client = Client::new(&url, Some("abc".to_string()), cache_dir.clone());
+
+        assert_eq!(client.input(1), Ok("+1\n-2\n".to_string()));
+
+        let (request_line, cookie, _) = server.join().unwrap();
+        assert_eq!(request_line, "GET /2018/day/1/input HTTP/1.1");
+        assert_eq!(cookie, "session=abc");
+
+        // The server is gone, so this can only succeed by reading the cache.
+        assert_eq!(client.input(1), Ok("+1\n-2\n".to_string()));
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn reports_http_errors() {
+        let (url, server) = serve("400 Bad Request", "Please log in");
+        let cache_dir = cache_dir("error");
+        let client = Client::new(&url, Some("abc".to_string()), cache_dir.clone());
+
+        assert!(client.input(2).is_err());
+        server.join().unwrap();
+        assert!(!cache_dir.exists());
+    }
+
+    #[test]
+    fn parses_outcomes() {
+        assert_eq!(
+            parse_outcome("<p>That's the right answer!  You are <em>one gold star</em></p>"),
+            Ok(Outcome::Correct)
+        );
+        assert_eq!(
+            parse_outcome("<p>That's not the right answer; your answer is too high.</p>"),
+            Ok(Outcome::TooHigh)
+        );
+        assert_eq!(
+            parse_outcome("<p>That's not the right answer; your answer is too low.</p>"),
+            Ok(Outcome::TooLow)
+        );
+        assert_eq!(
+            parse_outcome("<p>That's not the right answer.  If you're stuck, ...</p>"),
+            Ok(Outcome::Incorrect)
+        );
+        assert_eq!(
+            parse_outcome("<p>You gave an answer too recently. You have 37s left to wait.</p>"),
+            Ok(Outcome::Wait(Some("37s".to_string())))
+        );
+        assert_eq!(
+            parse_outcome("<p>You don't seem to be solving the right level.</p>"),
+            Ok(Outcome::WrongLevel)
+        );
+        assert!(parse_outcome("<p>Something else entirely</p>").is_err());
+    }
+
+    #[test]
+    fn submits_and_records_verdicts() {
+        let cache_dir = cache_dir("submit");
+
+        let (url, server) = serve(
+            "200 OK",
+            "That's not the right answer; your answer is too high.",
+        );
+        let client = Client::new(&url, Some("abc".to_string()), cache_dir.clone());
+
+        assert_eq!(client.submit(1, 2, "800"), Ok(Outcome::TooHigh));
+
+        let (request_line, cookie, body) = server.join().unwrap();
+        assert_eq!(request_line, "POST /2018/day/1/answer HTTP/1.1");
+        assert_eq!(cookie, "session=abc");
+        assert_eq!(body, "level=2&answer=800");
+
+        // Refused without contacting the (now absent) server.
+        assert!(client.submit(1, 2, "800").is_err());
+        assert!(client.submit(1, 2, "801").is_err());
+
+        let (url, server) = serve("200 OK", "You gave an answer too recently.");
+        let client = Client::new(&url, Some("abc".to_string()), cache_dir.clone());
+
+        assert_eq!(client.submit(1, 2, "790"), Ok(Outcome::Wait(None)));
+        server.join().unwrap();
+
+        let (url, server) = serve("200 OK", "That's the right answer!");
+        let client = Client::new(&url, Some("abc".to_string()), cache_dir.clone());
+
+        assert_eq!(client.submit(1, 2, "790"), Ok(Outcome::Correct));
+        server.join().unwrap();
+
+        assert!(client.submit(1, 2, "790").is_err());
+        assert_eq!(
+            fs::read_to_string(cache_dir.join("submissions")).unwrap(),
+            "1 2 high 800\n1 2 correct 790\n"
+        );
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    #[test]
+    fn requires_session() {
+        let client = Client::new("http://127.0.0.1:1", None, cache_dir("session"));
+
+        assert!(client.input(3).is_err());
+    }
+}
@@ -1,21 +1,23 @@
 use crate::error::{parse, re, require_with, Error, Result};
-use std::collections::HashMap;
-use std::iter::Iterator;
+use crate::grid::Grid;
+use crate::point::Point;
 use std::str::FromStr;
 
 #[derive(Debug)]
 struct Claim {
     id: i32,
-    x: usize,
-    y: usize,
-    width: usize,
-    height: usize,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
 }
 
 impl Claim {
-    fn squares(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
-        (self.x..self.x + self.width)
-            .flat_map(move |x| (self.y..self.y + self.height).map(move |y| (x, y)))
+    // The area of fabric which both claims cover.
+    fn shared(&self, other: &Claim) -> i64 {
+        let width = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let height = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+        i64::from(width.max(0)) * i64::from(height.max(0))
     }
 }
 
@@ -39,36 +41,191 @@ impl FromStr for Claim {
     }
 }
 
-pub fn part1(input: &str) -> Result<usize> {
+pub fn part1(input: &str) -> Result<i64> {
+    let fabric = Fabric::new(&parse_input(input)?);
+
+    Ok(fabric.overlaps[fabric.overlaps.max()])
+}
+
+pub fn part2(input: &str) -> Result<i32> {
     let claims = parse_input(input)?;
+    let fabric = Fabric::new(&claims);
 
-    let mut fabric = HashMap::new();
+    Ok(require_with!(
+        claims.iter().find(|c| fabric.overlap_area(c) == 0),
+        "all claims overlap!"
+    )
+    .id)
+}
 
-    for claim in claims.iter() {
-        for square in claim.squares() {
-            fabric.entry(square).and_modify(|n| *n += 1).or_insert(1);
+// The fabric cut along every edge of every claim, so that each claim covers a whole number of the
+// rectangular cells between the cuts. Working with cells rather than square inches keeps the size
+// down to the number of claims, however wide they are.
+struct Fabric {
+    xs: Vec<i32>,
+    ys: Vec<i32>,
+    // The area covered more than once above and to the left of each corner.
+    overlaps: Grid<i64>,
+}
+
+impl Fabric {
+    fn new(claims: &[Claim]) -> Self {
+        let xs = edges(claims.iter().flat_map(|c| vec![c.x, c.x + c.width]));
+        let ys = edges(claims.iter().flat_map(|c| vec![c.y, c.y + c.height]));
+        let corners = Point {
+            x: xs.len() as i32,
+            y: ys.len() as i32,
+        };
+
+        // The number of claims covering each cell, where cell (i, j) spans xs[i] to xs[i + 1] and
+        // ys[j] to ys[j + 1]. Mark the corners of each claim, then sum along the rows and columns
+        // to fill them in.
+        let mut cover = Grid::new(Point::ORIGIN, corners - Point { x: 1, y: 1 }, 0);
+        for claim in claims.iter() {
+            let (min, max) = (
+                cut(&xs, &ys, claim.x, claim.y),
+                cut(&xs, &ys, claim.x + claim.width, claim.y + claim.height),
+            );
+            cover[min] += 1;
+            cover[Point { x: max.x, y: min.y }] -= 1;
+            cover[Point { x: min.x, y: max.y }] -= 1;
+            cover[max] += 1;
+        }
+        for p in cover.points().collect::<Vec<_>>() {
+            cover[p] += prefix(&cover, p + Point::LEFT) + prefix(&cover, p + Point::UP)
+                - prefix(&cover, p + Point { x: -1, y: -1 });
         }
+
+        let mut overlaps = Grid::new(Point::ORIGIN, corners, 0);
+        for p in cover.points() {
+            let corner = p + Point { x: 1, y: 1 };
+            overlaps[corner] = doubled_area(&xs, &ys, &cover, p)
+                + overlaps[corner + Point::LEFT]
+                + overlaps[corner + Point::UP]
+                - overlaps[p];
+        }
+
+        Fabric { xs, ys, overlaps }
     }
 
-    Ok(fabric.values().filter(|n| **n >= 2).count())
+    // The area within the claim which other claims also cover.
+    fn overlap_area(&self, claim: &Claim) -> i64 {
+        let min = cut(&self.xs, &self.ys, claim.x, claim.y);
+        let max = cut(
+            &self.xs,
+            &self.ys,
+            claim.x + claim.width,
+            claim.y + claim.height,
+        );
+
+        self.overlaps[max]
+            - self.overlaps[Point { x: min.x, y: max.y }]
+            - self.overlaps[Point { x: max.x, y: min.y }]
+            + self.overlaps[min]
+    }
 }
 
-pub fn part2(input: &str) -> Result<i32> {
+#[derive(Clone, Copy, PartialEq)]
+enum Square {
+    Free,
+    Claimed(i32),
+    Overlap,
+}
+
+// A map of the fabric with a square per character, as in the puzzle text: each claim is drawn with
+// the last digit of its id, and squares claimed more than once with an X.
+pub fn render(input: &str) -> Result<String> {
     let claims = parse_input(input)?;
+    let max = Point {
+        x: claims.iter().map(|c| c.x + c.width).max().unwrap_or(0),
+        y: claims.iter().map(|c| c.y + c.height).max().unwrap_or(0),
+    };
+
+    let mut fabric = Grid::new(Point::ORIGIN, max, Square::Free);
+    for claim in claims.iter() {
+        for y in claim.y..claim.y + claim.height {
+            for x in claim.x..claim.x + claim.width {
+                let square = &mut fabric[Point { x, y }];
+                *square = match *square {
+                    Square::Free => Square::Claimed(claim.id),
+                    _ => Square::Overlap,
+                };
+            }
+        }
+    }
+
+    Ok(fabric.render(|square| match square {
+        Square::Free => '.',
+        Square::Claimed(id) => std::char::from_digit((id % 10).unsigned_abs(), 10).unwrap_or('?'),
+        Square::Overlap => 'X',
+    }))
+}
 
-    let mut fabric = HashMap::new();
+// A line for each claim, giving the area it shares with any other claim, then the claims it
+// overlaps and the area shared with each.
+pub fn report(input: &str) -> Result<String> {
+    let claims = parse_input(input)?;
+    let fabric = Fabric::new(&claims);
+    let mut s = String::new();
 
     for claim in claims.iter() {
-        for square in claim.squares() {
-            fabric.entry(square).and_modify(|n| *n += 1).or_insert(1);
+        s.push_str(&format!(
+            "#{} @ {},{}: {}x{}  overlap {}",
+            claim.id,
+            claim.x,
+            claim.y,
+            claim.width,
+            claim.height,
+            fabric.overlap_area(claim)
+        ));
+
+        let mut partners = claims
+            .iter()
+            .filter(|other| !std::ptr::eq(*other, claim))
+            .map(|other| (other.id, claim.shared(other)))
+            .filter(|&(_, area)| area > 0)
+            .peekable();
+
+        if partners.peek().is_some() {
+            s.push_str(" with");
+            for (id, area) in partners {
+                s.push_str(&format!(" #{} ({})", id, area));
+            }
         }
+        s.push('\n');
     }
 
-    Ok(require_with!(
-        claims.iter().find(|c| c.squares().all(|s| fabric[&s] == 1)),
-        "all claims overlap!"
-    )
-    .id)
+    Ok(s)
+}
+
+fn edges(coordinates: impl Iterator<Item = i32>) -> Vec<i32> {
+    let mut edges = coordinates.collect::<Vec<_>>();
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+// The corner of the cells where the cuts at x and y meet.
+fn cut(xs: &[i32], ys: &[i32], x: i32, y: i32) -> Point {
+    Point {
+        x: xs.binary_search(&x).unwrap_or(0) as i32,
+        y: ys.binary_search(&y).unwrap_or(0) as i32,
+    }
+}
+
+// The area of the cell if it is covered more than once. (The cells past the last edges are never
+// covered.)
+fn doubled_area(xs: &[i32], ys: &[i32], cover: &Grid<i32>, cell: Point) -> i64 {
+    if cover[cell] < 2 {
+        return 0;
+    }
+    let (i, j) = (cell.x as usize, cell.y as usize);
+    i64::from(xs[i + 1] - xs[i]) * i64::from(ys[j + 1] - ys[j])
+}
+
+// The running total at p, or 0 before the first row or column.
+fn prefix(grid: &Grid<i32>, p: Point) -> i32 {
+    grid.get(p).cloned().unwrap_or(0)
 }
 
 fn parse_input(input: &str) -> Result<Vec<Claim>> {
@@ -94,4 +251,40 @@ mod tests {
     fn sample_part2() {
         assert_eq!(part2(SAMPLE), Ok(3));
     }
+
+    #[test]
+    fn sample_render() {
+        assert_eq!(
+            render(SAMPLE),
+            Ok(
+                "........\n...2222.\n...2222.\n.11XX22.\n.11XX22.\n.111133.\n.111133.\n........\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn sample_report() {
+        assert_eq!(
+            report(SAMPLE),
+            Ok("#1 @ 1,3: 4x4  overlap 4 with #2 (4)\n\
+                #2 @ 3,1: 4x4  overlap 4 with #1 (4)\n\
+                #3 @ 5,5: 2x2  overlap 0\n"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn wide_claims() {
+        let claims = "
+            #1 @ 0,0: 5000x5000
+            #2 @ 4000,4000: 3000x3000
+            #3 @ 4500,100: 1000x1000
+            #4 @ 9000,0: 10x10
+            #5 @ 6999,6999: 1x1
+        ";
+
+        assert_eq!(part1(claims), Ok(1_000_000 + 500 * 1000 + 1));
+        assert_eq!(part2(claims), Ok(4));
+    }
 }
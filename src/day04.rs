@@ -2,9 +2,85 @@ use crate::error::{bail, parse, re, require_with, Error, Result};
 use std::collections::HashMap;
 use std::str::FromStr;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    fn days_in_month(&self) -> u8 {
+        match self.month {
+            4 | 6 | 9 | 11 => 30,
+            2 if self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0) => 29,
+            2 => 28,
+            _ => 31,
+        }
+    }
+
+    fn tomorrow(self) -> Date {
+        if self.day < self.days_in_month() {
+            Date {
+                day: self.day + 1,
+                ..self
+            }
+        } else if self.month < 12 {
+            Date {
+                month: self.month + 1,
+                day: 1,
+                ..self
+            }
+        } else {
+            Date {
+                year: self.year + 1,
+                month: 1,
+                day: 1,
+            }
+        }
+    }
+}
+
+// Ordered by date, then time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Timestamp {
+    date: Date,
+    hour: u8,
+    minute: u8,
+}
+
+impl Timestamp {
+    // The date of the midnight hour this time is closest to, so a shift starting at 23:58 is
+    // counted towards the following day.
+    fn shift_date(&self) -> Date {
+        if self.hour >= 12 {
+            self.date.tomorrow()
+        } else {
+            self.date
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let date = self.date;
+        (1..=12).contains(&date.month)
+            && (1..=date.days_in_month()).contains(&date.day)
+            && self.hour < 24
+            && self.minute < 60
+    }
+
+    // The minute of the midnight hour, or the start or end of the hour for times outside it.
+    fn midnight_minute(&self) -> u8 {
+        match self.hour {
+            0 => self.minute,
+            h if h >= 12 => 0,
+            _ => 60,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Log {
-    minute: u8,
+    time: Timestamp,
     event: Event,
 }
 
@@ -13,14 +89,27 @@ impl FromStr for Log {
 
     fn from_str(s: &str) -> Result<Self> {
         let caps = require_with!(
-            re(r"\[....-..-.. ..:(..)\] (.*)")?.captures(s),
+            re(r"\[(\d+)-(\d\d)-(\d\d) (\d\d):(\d\d)\] (.*)")?.captures(s),
             "couldn't parse log {}",
             s
         );
 
+        let time = Timestamp {
+            date: Date {
+                year: parse(&caps[1])?,
+                month: parse(&caps[2])?,
+                day: parse(&caps[3])?,
+            },
+            hour: parse(&caps[4])?,
+            minute: parse(&caps[5])?,
+        };
+        if !time.is_valid() {
+            bail!("no such date or time");
+        }
+
         Ok(Log {
-            minute: parse(&caps[1])?,
-            event: parse(&caps[2])?,
+            time,
+            event: parse(&caps[6])?,
         })
     }
 }
@@ -50,12 +139,18 @@ impl FromStr for Event {
 pub fn part1(input: &str) -> Result<i32> {
     let guards = parse_input(input)?;
 
-    let id = *guards
-        .keys()
-        .max_by_key(|g| guards[g].values().sum::<i32>())
-        .unwrap();
+    let id = *require_with!(
+        guards
+            .keys()
+            .max_by_key(|g| guards[g].values().sum::<i32>()),
+        "no guard ever falls asleep"
+    );
 
-    let minute = *guards[&id].keys().max_by_key(|m| guards[&id][m]).unwrap();
+    let minute = *require_with!(
+        guards[&id].keys().max_by_key(|m| guards[&id][m]),
+        "guard #{} never falls asleep",
+        id
+    );
 
     Ok(id * i32::from(minute))
 }
@@ -63,45 +158,116 @@ pub fn part1(input: &str) -> Result<i32> {
 pub fn part2(input: &str) -> Result<i32> {
     let guards = parse_input(input)?;
 
-    let (id, minute) = guards
-        .keys()
-        .flat_map(|g| (0..49).map(move |m| (g, m)))
-        .max_by_key(|(g, m)| guards[g].get(m).unwrap_or(&0))
-        .unwrap();
+    let (id, minute) = require_with!(
+        guards
+            .keys()
+            .flat_map(|g| (0..60).map(move |m| (g, m)))
+            .max_by_key(|(g, m)| guards[g].get(m).unwrap_or(&0)),
+        "no guard ever falls asleep"
+    );
 
     Ok(id * i32::from(minute))
 }
 
-fn parse_input(input: &str) -> Result<HashMap<i32, HashMap<u8, i32>>> {
-    let mut raw_logs = input.trim().lines().map(|l| l.trim()).collect::<Vec<_>>();
-    raw_logs.sort_unstable();
-    let logs = raw_logs
-        .iter()
-        .map(|l| parse::<Log>(l))
+// A guard's shift, with the minutes of the midnight hour they spent asleep.
+struct Shift {
+    date: Date,
+    guard: i32,
+    asleep: [bool; 60],
+}
+
+pub fn report(input: &str) -> Result<String> {
+    let mut s = "Date   ID     Minute\n".to_string();
+    for place in [10, 1].iter() {
+        s.push_str("             ");
+        s.extend((0..60).map(|m| std::char::from_digit(m / place % 10, 10).unwrap_or('?')));
+        s.push('\n');
+    }
+
+    for shift in shifts(input)? {
+        s.push_str(&format!(
+            "{:02}-{:02}  {:<5}  ",
+            shift.date.month,
+            shift.date.day,
+            format!("#{}", shift.guard)
+        ));
+        s.extend(shift.asleep.iter().map(|&a| if a { '#' } else { '.' }));
+        s.push('\n');
+    }
+
+    Ok(s)
+}
+
+// Checks that each guard only sleeps once at a time, and is awake by the end of their shift.
+fn shifts(input: &str) -> Result<Vec<Shift>> {
+    let mut logs = input
+        .trim()
+        .lines()
+        .map(|l| Ok((l.trim(), parse::<Log>(l.trim())?)))
         .collect::<Result<Vec<_>>>()?;
+    logs.sort_by_key(|(_, l)| l.time);
 
-    // Guard ID -> Minute -> times asleep at that minute
-    let mut guards = HashMap::new();
-    let mut on_duty = None;
+    let mut shifts: Vec<Shift> = Vec::new();
     let mut fell_asleep_at = None;
 
-    for log in logs {
+    for (line, log) in logs {
         match log.event {
-            Event::BeginsShift(id) => on_duty = Some(id),
-            Event::FallsAsleep => fell_asleep_at = Some(log.minute),
+            Event::BeginsShift(id) => {
+                if fell_asleep_at.is_some() {
+                    bail!("shift began while the last guard was asleep: {}", line);
+                }
+                shifts.push(Shift {
+                    date: log.time.shift_date(),
+                    guard: id,
+                    asleep: [false; 60],
+                })
+            }
+            Event::FallsAsleep => {
+                if shifts.is_empty() {
+                    bail!("fell asleep with no guard on duty: {}", line);
+                }
+                if fell_asleep_at.is_some() {
+                    bail!("fell asleep while already asleep: {}", line);
+                }
+                fell_asleep_at = Some(log.time.midnight_minute());
+            }
             Event::WakesUp => {
-                for m in require_with!(fell_asleep_at.take(), "guard not asleep!")..log.minute {
-                    guards
-                        .entry(require_with!(on_duty, "no guard on duty!"))
-                        .or_insert_with(HashMap::new)
-                        .entry(m)
-                        .and_modify(|s| *s += 1)
-                        .or_insert(1);
+                let shift =
+                    require_with!(shifts.last_mut(), "woke up with no guard on duty: {}", line);
+                for m in require_with!(
+                    fell_asleep_at.take(),
+                    "woke up without falling asleep: {}",
+                    line
+                )..log.time.midnight_minute()
+                {
+                    shift.asleep[m as usize] = true;
                 }
             }
         }
     }
 
+    if fell_asleep_at.is_some() {
+        bail!("log ended while a guard was asleep");
+    }
+
+    Ok(shifts)
+}
+
+fn parse_input(input: &str) -> Result<HashMap<i32, HashMap<u8, i32>>> {
+    // Guard ID -> Minute -> times asleep at that minute
+    let mut guards = HashMap::new();
+
+    for shift in shifts(input)? {
+        for m in (0..60).filter(|&m| shift.asleep[m as usize]) {
+            guards
+                .entry(shift.guard)
+                .or_insert_with(HashMap::new)
+                .entry(m)
+                .and_modify(|s| *s += 1)
+                .or_insert(1);
+        }
+    }
+
     Ok(guards)
 }
 
@@ -138,4 +304,116 @@ mod tests {
     fn sample_part2() {
         assert_eq!(part2(SAMPLE), Ok(4455));
     }
+
+    #[test]
+    fn sample_report() {
+        assert_eq!(
+            report(SAMPLE).unwrap(),
+            "\
+Date   ID     Minute
+             000000000011111111112222222222333333333344444444445555555555
+             012345678901234567890123456789012345678901234567890123456789
+11-01  #10    .....####################.....#########################.....
+11-02  #99    ........................................##########..........
+11-03  #10    ........................#####...............................
+11-04  #99    ....................................##########..............
+11-05  #99    .............................................##########.....
+"
+        );
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut lines = SAMPLE.trim().lines().collect::<Vec<_>>();
+        lines.reverse();
+
+        assert_eq!(part1(&lines.join("\n")), Ok(240));
+    }
+
+    #[test]
+    fn last_minutes() {
+        let logs = "
+            [1518-11-01 00:00] Guard #10 begins shift
+            [1518-11-01 00:05] falls asleep
+            [1518-11-01 00:08] wakes up
+            [1518-11-02 00:00] Guard #7 begins shift
+            [1518-11-02 00:57] falls asleep
+            [1518-11-02 00:59] wakes up
+            [1518-11-03 00:00] Guard #7 begins shift
+            [1518-11-03 00:58] falls asleep
+            [1518-11-03 01:00] wakes up
+        ";
+
+        assert_eq!(part1(logs), Ok(7 * 58));
+        assert_eq!(part2(logs), Ok(7 * 58));
+    }
+
+    #[test]
+    fn invalid_logs() {
+        let error = |logs: &str| part1(logs).unwrap_err().to_string();
+
+        assert_eq!(
+            error("[1518-11-01 00:05] falls asleep"),
+            "fell asleep with no guard on duty: [1518-11-01 00:05] falls asleep"
+        );
+        assert_eq!(
+            error(
+                "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:25] wakes up"
+            ),
+            "woke up without falling asleep: [1518-11-01 00:25] wakes up"
+        );
+        assert_eq!(
+            error(
+                "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-01 00:15] falls asleep"
+            ),
+            "fell asleep while already asleep: [1518-11-01 00:15] falls asleep"
+        );
+        assert_eq!(
+            error(
+                "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-01 23:58] Guard #99 begins shift"
+            ),
+            "shift began while the last guard was asleep: [1518-11-01 23:58] Guard #99 begins shift"
+        );
+        assert_eq!(
+            error(
+                "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep"
+            ),
+            "log ended while a guard was asleep"
+        );
+        assert_eq!(
+            error(
+                "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-01 00:75] wakes up"
+            ),
+            "failed to parse [1518-11-01 00:75] wakes up, no such date or time"
+        );
+        assert!(part1("[1518-11-01 24:00] Guard #10 begins shift").is_err());
+        assert!(part1("[1518-13-01 00:00] Guard #10 begins shift").is_err());
+        assert!(part1("[1518-02-29 00:00] Guard #10 begins shift").is_err());
+        assert!(part1("[1518-11-00 00:00] Guard #10 begins shift").is_err());
+        assert_eq!(
+            error("[1518-11-01 00:00] Guard #10 begins shift"),
+            "no guard ever falls asleep"
+        );
+        assert!(part2("[1518-11-01 00:00] Guard #10 begins shift").is_err());
+    }
+
+    #[test]
+    fn dates() {
+        let date = |year, month, day| Date { year, month, day };
+
+        assert_eq!(date(1518, 11, 30).tomorrow(), date(1518, 12, 1));
+        assert_eq!(date(1518, 12, 31).tomorrow(), date(1519, 1, 1));
+        assert_eq!(date(1518, 2, 28).tomorrow(), date(1518, 3, 1));
+        assert_eq!(date(1520, 2, 28).tomorrow(), date(1520, 2, 29));
+        assert_eq!(date(1600, 2, 28).tomorrow(), date(1600, 2, 29));
+        assert_eq!(date(1700, 2, 28).tomorrow(), date(1700, 3, 1));
+    }
 }
@@ -1,48 +1,190 @@
-use crate::error::Result;
+use crate::error::{bail, try_with, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::io::{ErrorKind, Read};
+use std::str;
 
 pub fn part1(input: &str) -> Result<usize> {
-    Ok(reduce(input.trim().as_bytes().iter()).len())
+    Ok(Rules::default()
+        .reduce_reader(input.as_bytes())?
+        .chars()
+        .count())
 }
 
 pub fn part2(input: &str) -> Result<usize> {
-    Ok((b'a'..=b'z')
-        .map(|unit| {
-            reduce(
-                input
-                    .trim()
-                    .as_bytes()
-                    .iter()
-                    .filter(|c| !unit.eq_ignore_ascii_case(c)),
-            )
-            .len()
-        })
-        .min()
-        .unwrap())
+    let rules = Rules::default();
+    Ok(rules.shortest_without_one_kind(&rules.reduce_reader(input.as_bytes())?))
 }
 
-fn reduce<'a>(chars: impl Iterator<Item = &'a u8>) -> Vec<&'a u8> {
-    let mut res: Vec<&u8> = Vec::new();
-    for x in chars {
-        match res.pop() {
-            Some(y) => {
-                if !reacts(*x, *y) {
-                    res.push(y);
-                    res.push(x);
+// Which pairs of adjacent units react, and the unit (if any) each reaction leaves behind.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    reactions: HashMap<(char, char), Option<char>>,
+    // Set only for the puzzle's own rules, which are checked without looking anything up, and
+    // applied to ASCII input a byte at a time.
+    ascii_polarity: bool,
+}
+
+impl Default for Rules {
+    // The puzzle's rules: a letter and the same letter in the other case destroy each other.
+    fn default() -> Self {
+        Rules {
+            ascii_polarity: true,
+            ..Rules::polarity('a'..='z')
+        }
+    }
+}
+
+impl Rules {
+    pub fn new() -> Self {
+        Rules {
+            reactions: HashMap::new(),
+            ascii_polarity: false,
+        }
+    }
+
+    // Each of the units destroys, and is destroyed by, its upper case form.
+    pub fn polarity(units: impl IntoIterator<Item = char>) -> Self {
+        let mut rules = Rules::new();
+        for unit in units {
+            let mut upper = unit.to_uppercase();
+            if let (Some(u), None) = (upper.next(), upper.next()) {
+                if u != unit {
+                    rules = rules.annihilate(unit, u);
+                }
+            }
+        }
+        rules
+    }
+
+    // x and y destroy each other when they meet, in either order.
+    pub fn annihilate(mut self, x: char, y: char) -> Self {
+        self.reactions.insert((x, y), None);
+        self.reactions.insert((y, x), None);
+        self.ascii_polarity = false;
+        self
+    }
+
+    // x followed by y becomes a single unit, which may go on to react with its new neighbour.
+    #[allow(dead_code)] // The puzzle never transforms units, so only the tests use this.
+    pub fn transform(mut self, x: char, y: char, into: char) -> Self {
+        self.reactions.insert((x, y), Some(into));
+        self.ascii_polarity = false;
+        self
+    }
+
+    pub fn reduce(&self, units: impl IntoIterator<Item = char>) -> String {
+        let mut res = String::new();
+        self.push_all(&mut res, units);
+        res
+    }
+
+    // Reduces a UTF-8 polymer as it is read, ignoring whitespace, so that only the units still
+    // unreacted need to be held in memory.
+    pub fn reduce_reader(&self, mut reader: impl Read) -> Result<String> {
+        let mut res = String::new();
+        let mut buf = [0; 64 * 1024];
+        // Read but not yet decoded, as a read can end part way through a character.
+        let mut pending = Vec::new();
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) if pending.is_empty() => return Ok(res),
+                Ok(0) => bail!("polymer ends part way through a character"),
+                Ok(n) => n,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => bail!("failed to read polymer: {}", err),
+            };
+            let mut read = &buf[..n];
+
+            if self.ascii_polarity && pending.is_empty() {
+                let ascii = read.iter().position(|b| !b.is_ascii()).unwrap_or(n);
+                push_ascii(&mut res, &read[..ascii]);
+                read = &read[ascii..];
+            }
+            pending.extend_from_slice(read);
+
+            let valid = match str::from_utf8(&pending) {
+                Ok(s) => s.len(),
+                Err(err) if err.error_len().is_none() => err.valid_up_to(),
+                Err(err) => bail!("polymer isn't valid UTF-8: {}", err),
+            };
+            let units = try_with!(str::from_utf8(&pending[..valid]), "invalid UTF-8");
+            self.push_all(&mut res, units.chars().filter(|c| !c.is_whitespace()));
+            pending.drain(..valid);
+        }
+    }
+
+    // The shortest polymer left after removing every unit of one kind (both of its cases) and
+    // reducing what remains.
+    //
+    // With confluent rules which pair each unit with another of the same kind, removing a kind
+    // can't stop any pair reacting, so this can be passed an already reduced polymer, and gives
+    // the same result with far less to scan.
+    pub fn shortest_without_one_kind(&self, polymer: &str) -> usize {
+        let kind = |c: char| c.to_lowercase().next().unwrap_or(c);
+        let kinds = polymer.chars().map(kind).collect::<BTreeSet<_>>();
+
+        kinds
+            .into_iter()
+            .map(|k| {
+                self.reduce(polymer.chars().filter(|&c| kind(c) != k))
+                    .chars()
+                    .count()
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    // What x becomes when it follows y: None if they don't react, Some(None) if they destroy
+    // each other, and Some(Some(unit)) if they combine.
+    fn react(&self, y: char, x: char) -> Option<Option<char>> {
+        if self.ascii_polarity {
+            Some(None).filter(|_| x != y && x.eq_ignore_ascii_case(&y))
+        } else {
+            self.reactions.get(&(y, x)).cloned()
+        }
+    }
+
+    // Adds each unit to the end of a reduced polymer, reacting it with the last unit if it can.
+    fn push_all(&self, res: &mut String, units: impl IntoIterator<Item = char>) {
+        for mut x in units {
+            loop {
+                let reaction = res.chars().next_back().and_then(|y| self.react(y, x));
+                match reaction {
+                    Some(None) => {
+                        res.pop();
+                    }
+                    Some(Some(into)) => {
+                        res.pop();
+                        x = into;
+                        continue;
+                    }
+                    None => res.push(x),
                 }
+                break;
             }
-            None => res.push(x),
         }
     }
-    res
 }
 
-fn reacts(x: u8, y: u8) -> bool {
-    x != y && x.eq_ignore_ascii_case(&y)
+// The puzzle's rules applied to ASCII bytes: a letter reacts with the byte for the same letter in
+// the other case, which differs from it in a single bit.
+fn push_ascii(res: &mut String, bytes: &[u8]) {
+    for &b in bytes {
+        match res.as_bytes().last() {
+            _ if char::from(b).is_whitespace() => {}
+            Some(&y) if y ^ b == 0x20 && b.is_ascii_alphabetic() => {
+                res.pop();
+            }
+            _ => res.push(char::from(b)),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io;
 
     #[test]
     fn sample_part1() {
@@ -53,4 +195,133 @@ mod tests {
     fn sample_part2() {
         assert_eq!(part2("dabAcCaCBAcCcaDA"), Ok(4));
     }
+
+    #[test]
+    fn long_stream() {
+        let n = 5_000_000;
+        let polymer = io::repeat(b'a')
+            .take(n)
+            .chain(&b"\n"[..])
+            .chain(io::repeat(b'A').take(n))
+            .chain(&b"dabAcCaCBAcCcaDA\n"[..]);
+
+        let rules = Rules::default();
+        let reduced = rules.reduce_reader(polymer).unwrap();
+        assert_eq!(reduced, "dabCBAcaDA");
+        assert_eq!(rules.shortest_without_one_kind(&reduced), 4);
+    }
+
+    #[test]
+    fn unicode() {
+        let rules = Rules::polarity("aαж".chars());
+        let polymer = "aαжЖΑAβ".as_bytes();
+
+        assert_eq!(rules.reduce_reader(polymer), Ok("β".to_string()));
+
+        // Characters split between reads.
+        let (front, back) = polymer.split_at(3);
+        assert_eq!(rules.reduce_reader(front.chain(back)), Ok("β".to_string()));
+
+        assert!(rules.reduce_reader(&polymer[..2]).is_err());
+
+        // The puzzle's rules, leaving and rejoining the ASCII fast path.
+        assert_eq!(
+            Rules::default().reduce_reader("dabAcCaCBAé\u{a0}cCcaDA".as_bytes()),
+            Ok("dabCBAécaDA".to_string())
+        );
+        assert!(rules.reduce_reader(&b"a\xff"[..]).is_err());
+    }
+
+    #[test]
+    fn transforms() {
+        let rules = Rules::new()
+            .transform('a', 'b', 'c')
+            .transform('c', 'c', 'd')
+            .annihilate('d', 'e');
+
+        assert!(!confluent(&rules));
+        assert_eq!(rules.reduce("ab".chars()), "c");
+        assert_eq!(rules.reduce("ba".chars()), "ba");
+        assert_eq!(rules.reduce("xcabex".chars()), "xx");
+    }
+
+    // Whether polymers reduce to the same result whatever order the reactions happen in, which is
+    // the case when the rules only destroy units and each unit has at most one partner. (With
+    // a-b and b-c both reacting, "abc" could become either "a" or "c".)
+    fn confluent(rules: &Rules) -> bool {
+        let mut partners = HashMap::new();
+        rules
+            .reactions
+            .iter()
+            .all(|(&(x, y), into)| into.is_none() && *partners.entry(x).or_insert(y) == y)
+    }
+
+    // A small xorshift generator, so the property tests are repeatable.
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    // Reduces by reacting a randomly chosen adjacent pair at a time.
+    fn reduce_in_random_order(rules: &Rules, polymer: &str, random: &mut Random) -> String {
+        let mut polymer = polymer.chars().collect::<Vec<_>>();
+        loop {
+            let pairs = (1..polymer.len())
+                .filter(|&i| rules.reactions.contains_key(&(polymer[i - 1], polymer[i])))
+                .collect::<Vec<_>>();
+            if pairs.is_empty() {
+                return polymer.into_iter().collect();
+            }
+            let i = pairs[random.below(pairs.len())];
+            polymer.drain(i - 1..=i);
+        }
+    }
+
+    #[test]
+    fn order_independent() {
+        let mut random = Random(0x5eed);
+        let units = "aAbBcCβΒ".chars().collect::<Vec<_>>();
+        let rule_sets = [
+            (Rules::default(), true),
+            (Rules::polarity("aβ".chars()), true),
+            (
+                Rules::new().annihilate('a', 'C').annihilate('b', 'b'),
+                false,
+            ),
+        ];
+
+        for (rules, by_kind) in rule_sets.iter() {
+            assert!(confluent(rules));
+
+            for _ in 0..200 {
+                let len = random.below(40);
+                let polymer = (0..len)
+                    .map(|_| units[random.below(units.len())])
+                    .collect::<String>();
+
+                let reduced = rules.reduce(polymer.chars());
+                assert_eq!(
+                    reduce_in_random_order(rules, &polymer, &mut random),
+                    reduced
+                );
+                if *by_kind {
+                    assert_eq!(
+                        rules.shortest_without_one_kind(&polymer),
+                        rules.shortest_without_one_kind(&reduced)
+                    );
+                }
+            }
+        }
+
+        let rules = Rules::new().annihilate('a', 'b').annihilate('b', 'c');
+        assert!(!confluent(&rules));
+        assert_eq!(rules.reduce("abc".chars()), "c");
+        assert_eq!(rules.reduce("cba".chars()), "a");
+    }
 }
@@ -1,99 +1,263 @@
-use crate::error::{parse, re, require_with, Result};
-use std::cmp::Ordering;
+use crate::error::{bail, parse, require_with, Result};
+use crate::grid::Grid;
+use crate::point::{Bounds, Point, PointN};
 use std::collections::{HashMap, HashSet};
 
 pub fn part1(input: &str) -> Result<i32> {
     let points = parse_input(input)?;
+    let (min, max) = bounds(&points)?;
+    largest_finite_area(&closest(&points, min, max))
+}
 
-    let min_x = *points.iter().map(|(x, _)| x).min().unwrap();
-    let max_x = *points.iter().map(|(x, _)| x).max().unwrap();
-    let min_y = *points.iter().map(|(_, y)| y).min().unwrap();
-    let max_y = *points.iter().map(|(_, y)| y).max().unwrap();
+pub fn part2(input: &str, total_distance: i32) -> Result<usize> {
+    let points = parse_input(input)?;
+    let (min, max) = search_bounds(&points, total_distance)?;
 
-    let mut areas = HashMap::new();
-    for &(x, y) in points.iter() {
-        areas.insert((x, y), 0);
-    }
-
-    for x in min_x..=max_x {
-        for y in min_y..=max_y {
-            if let Some(p) = unique_closest_point(&points, (x, y)) {
-                // The points with inifinite closest areas are the points that are closest to the
-                // boundary.
-                if x == min_x || x == max_x || y == min_y || y == max_y {
-                    areas.remove(p);
-                } else {
-                    areas.entry(*p).and_modify(|n| *n += 1);
+    // The total distance to a point is the total distance along x plus the total along y, so the
+    // two axes can be worked out separately and then paired up.
+    let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+    let ys = points.iter().map(|p| p.y).collect::<Vec<_>>();
+    let x_totals = axis_totals(&xs, min.x, max.x);
+    let mut y_totals = axis_totals(&ys, min.y, max.y);
+    y_totals.sort();
+
+    Ok(x_totals
+        .iter()
+        .map(|x| y_totals.partition_point(|y| x + y < total_distance))
+        .sum())
+}
+
+// Whether each position within reach of the coordinates is in the region of part two.
+fn safe_region(points: &[Point], total_distance: i32) -> Result<Grid<bool>> {
+    let (min, max) = search_bounds(points, total_distance)?;
+
+    let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+    let ys = points.iter().map(|p| p.y).collect::<Vec<_>>();
+    let x_totals = axis_totals(&xs, min.x, max.x);
+    let y_totals = axis_totals(&ys, min.y, max.y);
+
+    Ok(Grid::from_fn(min, max, |q| {
+        x_totals[(q.x - min.x) as usize] + y_totals[(q.y - min.y) as usize] < total_distance
+    }))
+}
+
+// The total distance from the coordinates to each position from min to max inclusive.
+fn axis_totals(coordinates: &[i32], min: i32, max: i32) -> Vec<i32> {
+    let mut coordinates = coordinates.to_vec();
+    coordinates.sort();
+    let n = coordinates.len() as i32;
+
+    let mut totals = Vec::with_capacity((max - min + 1).max(0) as usize);
+    let mut total = coordinates.iter().map(|c| (c - min).abs()).sum::<i32>();
+    let mut passed = 0;
+
+    for x in min..=max {
+        totals.push(total);
+        while passed < coordinates.len() && coordinates[passed] <= x {
+            passed += 1;
+        }
+        // One step further from the coordinates at or before x, and one closer to the rest.
+        total += passed as i32 - (n - passed as i32);
+    }
+
+    totals
+}
+
+// The index of the unique closest point to each position in the box, found by flooding outwards
+// from all the points at once. A position reached at the same distance from two different points
+// is a tie, and passes the tie on to the positions it reaches in turn.
+//
+// The shortest routes between positions in the box stay inside it, so distances through the
+// flood are the same as Manhattan distances.
+fn closest(points: &[Point], min: Point, max: Point) -> Grid<Option<usize>> {
+    // The distance each position was reached at, and its closest point if that is unique.
+    let mut reached: Grid<Option<(i32, Option<usize>)>> = Grid::new(min, max, None);
+    let mut frontier = Vec::new();
+    for (i, &p) in points.iter().enumerate() {
+        reached[p] = Some((0, Some(i)));
+        frontier.push(p);
+    }
+
+    let mut distance = 0;
+    while !frontier.is_empty() {
+        distance += 1;
+        let mut next = Vec::new();
+
+        for &q in frontier.iter() {
+            let owner = reached[q].and_then(|(_, owner)| owner);
+
+            for &step in [Point::UP, Point::LEFT, Point::RIGHT, Point::DOWN].iter() {
+                let r = q + step;
+                match reached.get(r).cloned() {
+                    Some(None) => {
+                        reached[r] = Some((distance, owner));
+                        next.push(r);
+                    }
+                    Some(Some((d, o))) if d == distance && o != owner => {
+                        reached[r] = Some((d, None));
+                    }
+                    _ => {}
                 }
             }
         }
+
+        frontier = next;
     }
 
-    Ok(*areas.values().max().unwrap())
+    Grid::from_fn(min, max, |q| reached[q].and_then(|(_, owner)| owner))
 }
 
-pub fn part2(input: &str, total_distance: i32) -> Result<usize> {
-    let points = parse_input(input)?;
+fn largest_finite_area(closest: &Grid<Option<usize>>) -> Result<i32> {
+    let infinite = infinite_areas(closest);
+
+    // Any point outside the bounding box with a unique closest point has it on the box's boundary
+    // too, so the finite areas all lie inside.
+    let mut areas = HashMap::new();
+    for &i in closest.values().flatten() {
+        if !infinite.contains(&i) {
+            *areas.entry(i).or_insert(0) += 1;
+        }
+    }
 
-    // It is feasible that we would need to check a larger area than this, but it turns out this
-    // isn't required for the correct solution.
-    let min_x = *points.iter().map(|(x, _)| x).min().unwrap();
-    let max_x = *points.iter().map(|(x, _)| x).max().unwrap();
-    let min_y = *points.iter().map(|(_, y)| y).min().unwrap();
-    let max_y = *points.iter().map(|(_, y)| y).max().unwrap();
-
-    Ok((min_x..=max_x)
-        .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
-        .filter(|q| points.iter().map(|p| distance(*p, *q)).sum::<i32>() < total_distance)
-        .count())
+    Ok(*require_with!(
+        areas.values().max(),
+        "every area is infinite"
+    ))
 }
 
-fn distance(p: (i32, i32), q: (i32, i32)) -> i32 {
-    (p.0 - q.0).abs() + (p.1 - q.1).abs()
+// The points whose closest areas go on forever.
+//
+// Beyond the right hand edge of the bounding box every point is to the left, so moving further
+// right adds the same distance to every point and can't change which is closest. The same goes
+// for the other edges, so any area reaching the edge of the box goes on forever, and any area
+// which doesn't stays inside.
+fn infinite_areas(closest: &Grid<Option<usize>>) -> HashSet<usize> {
+    closest
+        .iter()
+        .filter(|&(q, _)| closest.on_boundary(q))
+        .filter_map(|(_, &i)| i)
+        .collect()
 }
 
-fn unique_closest_point<'a>(
-    points: &'a HashSet<(i32, i32)>,
-    q: (i32, i32),
-) -> Option<&'a (i32, i32)> {
-    let (min, unique) = points.iter().fold((None, true), |(min, unique), p| {
-        if let Some((_, min_dist)) = min {
-            let dist = distance(*p, q);
-            match dist.cmp(&min_dist) {
-                Ordering::Less => (Some((p, dist)), true),
-                Ordering::Equal => (min, false),
-                Ordering::Greater => (min, unique),
-            }
-        } else {
-            (Some((p, distance(*p, q))), unique)
-        }
-    });
+// The closest areas as in the puzzle text, with a border of one position around the coordinates:
+// each coordinate is drawn as an upper case letter, its area in lower case, and ties as dots.
+pub fn render(input: &str) -> Result<String> {
+    let points = parse_input(input)?;
+    if points.len() > 26 {
+        bail!("can only draw up to 26 coordinates, not {}", points.len());
+    }
+
+    let (min, max) = bounds(&points)?;
+    let border = Point { x: 1, y: 1 };
+    let closest = closest(&points, min - border, max + border);
+    let letter = |i: usize| (b'a' + i as u8) as char;
+
+    Ok(closest.render_points(|q, i| match i {
+        Some(i) if points[*i] == q => letter(*i).to_ascii_uppercase(),
+        Some(i) => letter(*i),
+        None => '.',
+    }))
+}
+
+// A binary PPM image of the closest areas, each in its own colour with ties in grey, and with the
+// coordinates in black and the edge of the region from part two in white.
+pub fn image(input: &str, total_distance: i32) -> Result<Vec<u8>> {
+    let points = parse_input(input)?;
+    let safe = safe_region(&points, total_distance)?;
+
+    // Enough to show all the coordinates and the whole of the region.
+    let shown = points
+        .iter()
+        .cloned()
+        .chain(safe.iter().filter(|(_, &s)| s).map(|(q, _)| q));
+    let bounds = require_with!(
+        Bounds::from_points(shown.map(PointN::from)),
+        "no coordinates"
+    );
+    let border = Point { x: 1, y: 1 };
+    let closest = closest(
+        &points,
+        Point::from(bounds.min) - border,
+        Point::from(bounds.max) + border,
+    );
 
-    if unique {
-        min.map(|(p, _)| p)
-    } else {
-        None
+    let in_region = |q| safe.get(q).cloned().unwrap_or(false);
+    let on_edge = |q| in_region(q) && closest.neighbours4(q).any(|r| !in_region(r));
+
+    let mut ppm = format!("P6\n{} {}\n255\n", closest.width(), closest.height()).into_bytes();
+    for (q, i) in closest.iter() {
+        ppm.extend_from_slice(&match i {
+            Some(i) if points[*i] == q => [0, 0, 0],
+            _ if on_edge(q) => [255, 255, 255],
+            Some(i) => colour(*i),
+            None => [128, 128, 128],
+        });
     }
+
+    Ok(ppm)
 }
 
-fn parse_input(input: &str) -> Result<HashSet<(i32, i32)>> {
-    input
-        .trim()
-        .lines()
-        .map(|l| {
-            let caps = require_with!(
-                re(r"(\d+),\s(\d+)")?.captures(l),
-                "couldn't parse coordinate {}",
-                l,
-            );
-            Ok((parse(&caps[1])?, parse(&caps[2])?))
-        })
-        .collect()
+// A bright colour for each index, with hues spread by the golden angle so that neighbouring
+// indices look quite different.
+fn colour(i: usize) -> [u8; 3] {
+    let h = (i as f64 * 137.508) % 360.0 / 60.0;
+    let (v, s) = (0.95, 0.6);
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let byte = |a: f64| ((a + v - c) * 255.0).round() as u8;
+    [byte(r), byte(g), byte(b)]
+}
+
+// The corners of the smallest rectangle containing all the points.
+fn bounds(points: &[Point]) -> Result<(Point, Point)> {
+    let bounds = require_with!(
+        Bounds::from_points(points.iter().map(|&p| PointN::from(p))),
+        "no coordinates"
+    );
+
+    Ok((bounds.min.into(), bounds.max.into()))
+}
+
+// The corners of the area which could hold points of the region in part two. Each step away from
+// the bounding box takes a point one step further from every coordinate, so no point further out
+// than the margin can be in the region.
+fn search_bounds(points: &[Point], total_distance: i32) -> Result<(Point, Point)> {
+    let (min, max) = bounds(points)?;
+    let margin = (total_distance - 1).max(0) / points.len() as i32;
+    let margin = Point {
+        x: margin,
+        y: margin,
+    };
+
+    Ok((min - margin, max + margin))
+}
+
+// The distinct coordinates, in the order they are listed.
+fn parse_input(input: &str) -> Result<Vec<Point>> {
+    let mut seen = HashSet::new();
+    let mut points = Vec::new();
+    for l in input.trim().lines() {
+        let p = parse(l.trim())?;
+        if seen.insert(p) {
+            points.push(p);
+        }
+    }
+    Ok(points)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cmp::Ordering;
+    use std::time::Instant;
 
     const SAMPLE: &'static str = "
         1, 1
@@ -104,6 +268,8 @@ mod tests {
         8, 9
     ";
 
+    const INPUT: &'static str = include_str!("input/day06");
+
     #[test]
     fn sample_part1() {
         assert_eq!(part1(SAMPLE), Ok(17));
@@ -113,4 +279,182 @@ mod tests {
     fn sample_part2() {
         assert_eq!(part2(SAMPLE, 32), Ok(16));
     }
+
+    // Checks every point against every position, for comparison with the faster versions.
+    fn unique_closest_point(points: &[Point], q: Point) -> Option<usize> {
+        let (min, unique) =
+            points
+                .iter()
+                .enumerate()
+                .fold((None, true), |(min, unique), (i, p)| {
+                    let dist = p.manhattan(q);
+                    match min {
+                        None => (Some((i, dist)), true),
+                        Some((_, min_dist)) => match dist.cmp(&min_dist) {
+                            Ordering::Less => (Some((i, dist)), true),
+                            Ordering::Equal => (min, false),
+                            Ordering::Greater => (min, unique),
+                        },
+                    }
+                });
+
+        if unique {
+            min.map(|(i, _)| i)
+        } else {
+            None
+        }
+    }
+
+    fn naive_part1(input: &str) -> Result<i32> {
+        let points = parse_input(input)?;
+        let (min, max) = bounds(&points)?;
+        let closest = Grid::from_fn(min, max, |q| unique_closest_point(&points, q));
+        largest_finite_area(&closest)
+    }
+
+    fn naive_part2(input: &str, total_distance: i32) -> Result<usize> {
+        let points = parse_input(input)?;
+        let (min, max) = search_bounds(&points, total_distance)?;
+
+        let distances = Grid::from_fn(min, max, |q| {
+            points.iter().map(|p| p.manhattan(q)).sum::<i32>()
+        });
+        Ok(distances.values().filter(|&&d| d < total_distance).count())
+    }
+
+    #[test]
+    fn flood_matches_naive() {
+        for input in [SAMPLE, INPUT, "0, 0\n4, 0\n0, 4\n4, 4\n2, 2\n2, 3"].iter() {
+            let points = parse_input(input).unwrap();
+            let (min, max) = bounds(&points).unwrap();
+
+            assert_eq!(
+                Grid::from_fn(min, max, |q| unique_closest_point(&points, q)),
+                closest(&points, min, max)
+            );
+            assert_eq!(part1(input), naive_part1(input));
+        }
+    }
+
+    #[test]
+    fn separable_sums_match_naive() {
+        assert_eq!(part2(INPUT, 10000), naive_part2(INPUT, 10000));
+        for total_distance in 0..60 {
+            assert_eq!(
+                part2(SAMPLE, total_distance),
+                naive_part2(SAMPLE, total_distance)
+            );
+        }
+    }
+
+    #[test]
+    fn infinite_areas_match_a_larger_grid() {
+        let points = parse_input(SAMPLE).unwrap();
+        let (min, max) = bounds(&points).unwrap();
+        let margin = Point { x: 20, y: 20 };
+
+        let area = |i, min, max| {
+            Grid::new(min, max, ())
+                .points()
+                .filter(|&q| unique_closest_point(&points, q) == Some(i))
+                .count()
+        };
+
+        let infinite = infinite_areas(&closest(&points, min, max));
+        assert_eq!(infinite.len(), 4);
+        for i in 0..points.len() {
+            let inside = area(i, min, max);
+            let larger = area(i, min - margin, max + margin);
+            assert_eq!(infinite.contains(&i), larger > inside);
+        }
+    }
+
+    #[test]
+    fn region_beyond_bounds() {
+        // Everything within 9 steps of the only coordinate.
+        assert_eq!(part2("5, 5", 10), Ok(181));
+        assert_eq!(part2("0, 0\n2, 0", 5), Ok(11));
+        assert!(part1("1, 1\n5, 5").is_err());
+    }
+
+    #[test]
+    fn sample_render() {
+        assert_eq!(
+            render(SAMPLE).unwrap(),
+            "\
+aaaaa.cccc
+aAaaa.cccc
+aaaddecccc
+aadddeccCc
+..dDdeeccc
+bb.deEeecc
+bBb.eeee..
+bbb.eeefff
+bbb.eeffff
+bbb.ffffFf
+bbb.ffffff
+"
+        );
+        assert!(render(&(0..27).map(|i| format!("{}, 0\n", i)).collect::<String>()).is_err());
+    }
+
+    #[test]
+    fn sample_image() {
+        let ppm = image(SAMPLE, 32).unwrap();
+        let header = b"P6\n10 11\n255\n";
+        assert_eq!(&ppm[..header.len()], &header[..]);
+        assert_eq!(ppm.len(), header.len() + 10 * 11 * 3);
+
+        // The image starts at (0, 0).
+        let pixel = |x: usize, y: usize| {
+            let i = header.len() + (y * 10 + x) * 3;
+            [ppm[i], ppm[i + 1], ppm[i + 2]]
+        };
+        assert_eq!(pixel(1, 1), [0, 0, 0]);
+        assert_eq!(pixel(0, 4), [128, 128, 128]);
+        assert_eq!(pixel(0, 0), colour(0));
+        // (3, 3) is at the top of the region, and (4, 4) in the middle of it.
+        assert_eq!(pixel(3, 3), [255, 255, 255]);
+        assert_eq!(pixel(4, 4), colour(3));
+    }
+
+    #[test]
+    fn colours() {
+        let colours = (0..50).map(colour).collect::<HashSet<_>>();
+        assert_eq!(colours.len(), 50);
+        assert!(!colours.contains(&[0, 0, 0]));
+        assert!(!colours.contains(&[128, 128, 128]));
+        assert!(!colours.contains(&[255, 255, 255]));
+    }
+
+    // Compares the flood fill and separable sums with checking every point against every position,
+    // on the puzzle input. Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn benchmark() {
+        fn time<T>(f: impl Fn() -> T) -> (T, f64) {
+            let start = Instant::now();
+            let mut res = f();
+            for _ in 1..10 {
+                res = f();
+            }
+            (res, start.elapsed().as_secs_f64() * 100.0)
+        }
+
+        let runs = [
+            (
+                "part 1 naive",
+                time(|| naive_part1(INPUT).map(|a| a as usize)),
+            ),
+            ("part 1 flood", time(|| part1(INPUT).map(|a| a as usize))),
+            ("part 2 naive", time(|| naive_part2(INPUT, 10000))),
+            ("part 2 separable", time(|| part2(INPUT, 10000))),
+        ];
+
+        for (name, (answer, ms)) in runs.iter() {
+            println!("{:<16}  {:?}  {:>8.3}ms", name, answer, ms);
+        }
+        assert_eq!(runs[0].1 .0, runs[1].1 .0);
+        assert_eq!(runs[2].1 .0, runs[3].1 .0);
+    }
 }
@@ -1,119 +1,474 @@
-use crate::error::{parse, re, require_with, Result};
-use std::collections::{HashMap, HashSet};
+use crate::error::{bail, parse, re, require_with, Result};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::fmt;
 
+// A lone worker takes the steps in the same order whatever they take, so this needs no times.
 pub fn part1(input: &str) -> Result<String> {
-    work(input, 1, 0).map(|(s, _)| s)
+    Ok(join(parse_input(input)?.topological_order().into_iter()))
 }
 
 pub fn part2(input: &str, workers: u8, base_seconds: u32) -> Result<u32> {
-    work(input, workers, base_seconds).map(|(_, t)| t)
+    Ok(work(&parse_input(input)?, workers, base_seconds)?.makespan())
 }
 
-struct Job {
-    step: char,
-    time_remaining: u32,
+pub fn schedule(input: &str, workers: u8, base_seconds: u32) -> Result<Schedule> {
+    work(&parse_input(input)?, workers, base_seconds)
 }
 
-fn work(input: &str, workers: u8, base_seconds: u32) -> Result<(String, u32)> {
-    let Project {
-        mut ready,
-        blocks,
-        mut blocked_by,
-    } = parse_input(input)?;
+// How the time taken depends on the number of workers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    // The longest chain of steps which each block the next, and the time it takes. No number of
+    // workers can finish any sooner.
+    pub critical_path: Vec<String>,
+    pub critical_time: u32,
+    // The fewest workers which finish in the critical time, following the puzzle's rules for
+    // choosing steps.
+    pub min_workers: u8,
+    // The time taken by 1, 2, ... workers.
+    pub makespans: Vec<u32>,
+}
 
-    let mut in_progress = HashMap::new();
-    let mut idle: HashSet<_> = (0..workers).collect();
-    let mut steps = String::new();
-    let mut t = 0;
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Critical path: {} ({}s)",
+            self.critical_path.join(" -> "),
+            self.critical_time
+        )?;
+        writeln!(f, "Fewest workers to match it: {}", self.min_workers)?;
+        writeln!(f, "Workers   Time")?;
+        for (i, t) in self.makespans.iter().enumerate() {
+            writeln!(f, "{:>7}  {:>5}", i + 1, t)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn analyse(input: &str, max_workers: u8, base_seconds: u32) -> Result<Analysis> {
+    let project = parse_input(input)?;
+    let (critical_time, critical_path) = project.critical_path(base_seconds)?;
+
+    let makespan = |workers| Ok(work(&project, workers, base_seconds)?.makespan());
+    let makespans = (1..=max_workers)
+        .map(makespan)
+        .collect::<Result<Vec<_>>>()?;
+
+    // Adding workers can occasionally slow things down, when an early start on one step delays a
+    // more important one, so check each number in turn rather than searching.
+    let mut min_workers = None;
+    for workers in 1..=u8::MAX {
+        let t = match makespans.get(workers as usize - 1) {
+            Some(&t) => t,
+            None => makespan(workers)?,
+        };
+        if t == critical_time {
+            min_workers = Some(workers);
+            break;
+        }
+    }
+
+    Ok(Analysis {
+        critical_path: critical_path.into_iter().map(String::from).collect(),
+        critical_time,
+        min_workers: require_with!(
+            min_workers,
+            "no number of workers matches the critical path"
+        ),
+        makespans,
+    })
+}
+
+// A step worked on from `start` until just before `end`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    pub worker: u8,
+    pub step: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+const MAX_DRAWN_SECONDS: u32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub workers: u8,
+    // In the order they were started.
+    pub tasks: Vec<Task>,
+}
+
+impl Schedule {
+    // The time until every step is complete.
+    pub fn makespan(&self) -> u32 {
+        self.tasks.iter().map(|task| task.end).max().unwrap_or(0)
+    }
+
+    // As in the puzzle text, a row for each second up to the end, giving what each worker is doing
+    // and the steps done so far (in the order they were finished).
+    pub fn table(&self) -> Result<String> {
+        self.check_drawable()?;
+        let name_width = self.tasks.iter().map(|t| t.step.len()).max().unwrap_or(1);
+        let width = (name_width + 7).max(11);
+
+        let mut s = "Second".to_string();
+        for worker in 1..=self.workers {
+            s.push_str(&format!("{:>w$}", format!("Worker {}", worker), w = width));
+        }
+        s.push_str("   Done\n");
+
+        let mut done = self.tasks.clone();
+        done.sort_by_key(|task| task.end);
 
-    while !ready.is_empty() || idle.len() != workers as usize {
-        for worker in idle.clone().iter() {
-            in_progress.remove(worker);
-            ready.sort_unstable_by(|a, b| b.cmp(a));
-            if let Some(step) = ready.pop() {
-                steps.push(step);
-                idle.remove(&worker);
-                in_progress.insert(
-                    *worker,
-                    Job {
-                        step,
-                        time_remaining: time_to_complete(base_seconds, step),
-                    },
-                );
+        for t in 0..=self.makespan() {
+            s.push_str(&format!("{:>4}  ", t));
+            for worker in 0..self.workers {
+                let step = self.working_on(worker, t).map_or(".", |task| &task.step);
+                s.push_str(&format!("      {:<w$}", step, w = width - 6));
             }
+            s.push_str("   ");
+            s.push_str(&join(
+                done.iter()
+                    .filter(|task| task.end <= t)
+                    .map(|task| task.step.as_str()),
+            ));
+            s.truncate(s.trim_end().len());
+            s.push('\n');
         }
 
-        t += 1;
-
-        for (worker, job) in in_progress.iter_mut() {
-            job.time_remaining -= 1;
-            if job.time_remaining == 0 {
-                idle.insert(*worker);
-                if let Some(blocked) = blocks.get(&job.step) {
-                    for b in blocked {
-                        if let Some(blocking) = blocked_by.get_mut(&b) {
-                            blocking.remove(&job.step);
-                            if blocking.is_empty() {
-                                ready.push(*b);
-                            }
-                        }
+        Ok(s)
+    }
+
+    // A row for each worker, with a character per second. Steps with single character names fill
+    // their time with their name; longer names are written once and followed by dashes.
+    pub fn gantt(&self) -> Result<String> {
+        self.check_drawable()?;
+        let mut s = String::new();
+        for worker in 0..self.workers {
+            s.push_str(&format!("Worker {:<3} ", worker + 1));
+            let mut t = 0;
+            while t < self.makespan() {
+                match self.working_on(worker, t) {
+                    Some(task) => {
+                        let len = (task.end - task.start) as usize;
+                        let label = if task.step.chars().count() == 1 {
+                            task.step.repeat(len)
+                        } else {
+                            format!("{:-<w$}", task.step, w = len)
+                        };
+                        s.extend(label.chars().take(len));
+                        t = task.end;
+                    }
+                    None => {
+                        s.push('.');
+                        t += 1;
                     }
                 }
             }
+            s.push('\n');
         }
+        Ok(s)
     }
 
-    Ok((steps, t))
+    // Both drawings take space (and time) for every second, which soon stops being useful.
+    fn check_drawable(&self) -> Result<()> {
+        if self.makespan() > MAX_DRAWN_SECONDS {
+            bail!(
+                "{} seconds is too long to draw a second at a time",
+                self.makespan()
+            );
+        }
+        Ok(())
+    }
+
+    fn working_on(&self, worker: u8, t: u32) -> Option<&Task> {
+        self.tasks
+            .iter()
+            .find(|task| task.worker == worker && task.start <= t && t < task.end)
+    }
 }
 
-fn time_to_complete(base_seconds: u32, c: char) -> u32 {
-    base_seconds + u32::from(*c.to_string().as_bytes().iter().next().unwrap()) - 64
+// Step names run together as in the puzzle if they're all single characters, or separated by
+// commas otherwise.
+fn join<'a>(steps: impl Iterator<Item = &'a str>) -> String {
+    let steps = steps.collect::<Vec<_>>();
+    if steps.iter().all(|s| s.chars().count() == 1) {
+        steps.concat()
+    } else {
+        steps.join(",")
+    }
 }
 
+// Jumps from one step finishing to the next rather than ticking through every second. Whenever
+// workers are free, the lowest numbered ones take the ready steps in alphabetical order.
+fn work(project: &Project, workers: u8, base_seconds: u32) -> Result<Schedule> {
+    if workers == 0 {
+        bail!("need at least one worker");
+    }
+
+    let mut waiting_on = project
+        .steps
+        .iter()
+        .map(|step| (step.as_str(), project.blocking(step).count()))
+        .collect::<HashMap<_, _>>();
+    let mut ready = waiting_on
+        .iter()
+        .filter(|(_, &n)| n == 0)
+        .map(|(&step, _)| step)
+        .collect::<BTreeSet<_>>();
+    let mut idle = (0..workers).collect::<BTreeSet<_>>();
+
+    // The tasks in progress, soonest finishing first.
+    let mut in_progress = BinaryHeap::new();
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut t = 0;
+
+    loop {
+        while let (Some(&worker), Some(&step)) = (idle.iter().next(), ready.iter().next()) {
+            idle.remove(&worker);
+            ready.remove(step);
+            let end = project.finish(step, t, base_seconds)?;
+            in_progress.push(Reverse((end, worker, tasks.len())));
+            tasks.push(Task {
+                worker,
+                step: step.to_string(),
+                start: t,
+                end,
+            });
+        }
+
+        t = match in_progress.peek() {
+            Some(Reverse((end, _, _))) => *end,
+            None => break,
+        };
+
+        while let Some(&Reverse((end, worker, i))) = in_progress.peek() {
+            if end != t {
+                break;
+            }
+            in_progress.pop();
+            idle.insert(worker);
+
+            for step in project.blocks(&tasks[i].step) {
+                if let Some(n) = waiting_on.get_mut(step.as_str()) {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.insert(step);
+                    }
+                }
+            }
+        }
+    }
+
+    let blocked = waiting_on
+        .into_iter()
+        .filter(|&(_, n)| n > 0)
+        .map(|(step, _)| step)
+        .collect::<BTreeSet<_>>();
+    if !blocked.is_empty() {
+        bail!(
+            "every worker is idle but {} can't start",
+            join(blocked.into_iter())
+        );
+    }
+
+    Ok(Schedule { workers, tasks })
+}
+
+#[derive(Debug)]
 struct Project {
-    ready: Vec<char>,
-    blocks: HashMap<char, HashSet<char>>,
-    blocked_by: HashMap<char, HashSet<char>>,
+    steps: BTreeSet<String>,
+    blocks: HashMap<String, BTreeSet<String>>,
+    blocked_by: HashMap<String, BTreeSet<String>>,
+    durations: HashMap<String, u32>,
+}
+
+impl Project {
+    // The steps which can't begin until this one is finished.
+    fn blocks(&self, step: &str) -> impl Iterator<Item = &String> {
+        self.blocks.get(step).into_iter().flatten()
+    }
+
+    // The steps which must be finished before this one can begin.
+    fn blocking(&self, step: &str) -> impl Iterator<Item = &String> {
+        self.blocked_by.get(step).into_iter().flatten()
+    }
+
+    // The time given in the input if there is one, or for the puzzle's single letter steps,
+    // base_seconds plus the letter's position in the alphabet.
+    fn duration(&self, step: &str, base_seconds: u32) -> Result<u32> {
+        if let Some(&d) = self.durations.get(step) {
+            return Ok(d);
+        }
+
+        let mut chars = step.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_uppercase() => Ok(require_with!(
+                base_seconds.checked_add(u32::from(c) - u32::from('A') + 1),
+                "step {} takes too long to count",
+                step
+            )),
+            _ => bail!("no time given for step {}", step),
+        }
+    }
+
+    // When the step finishes if it starts at the given time.
+    fn finish(&self, step: &str, start: u32, base_seconds: u32) -> Result<u32> {
+        Ok(require_with!(
+            start.checked_add(self.duration(step, base_seconds)?),
+            "step {} finishes too late to count",
+            step
+        ))
+    }
+
+    // The longest chain of steps which each block the next, and its total time.
+    fn critical_path(&self, base_seconds: u32) -> Result<(u32, Vec<&str>)> {
+        // The time each step finishes with unlimited workers, and the step it waited for last.
+        let mut finish: HashMap<&str, (u32, Option<&str>)> = HashMap::new();
+
+        for step in self.topological_order() {
+            let before = self
+                .blocking(step)
+                .map(|b| (finish[b.as_str()].0, b.as_str()))
+                .max();
+            let start = before.map_or(0, |(t, _)| t);
+            finish.insert(
+                step,
+                (
+                    self.finish(step, start, base_seconds)?,
+                    before.map(|(_, b)| b),
+                ),
+            );
+        }
+
+        let (mut step, &(time, _)) = require_with!(
+            finish
+                .iter()
+                .max_by_key(|(step, (t, _))| (*t, Reverse(*step))),
+            "no steps"
+        );
+        let mut path = vec![*step];
+        while let Some((_, Some(before))) = finish.get(step) {
+            path.push(before);
+            step = before;
+        }
+        path.reverse();
+
+        Ok((time, path))
+    }
+
+    // Every step, each after all the steps blocking it.
+    fn topological_order(&self) -> Vec<&str> {
+        let mut waiting_on = self
+            .steps
+            .iter()
+            .map(|step| (step.as_str(), self.blocking(step).count()))
+            .collect::<HashMap<_, _>>();
+        let mut ready = waiting_on
+            .iter()
+            .filter(|(_, &n)| n == 0)
+            .map(|(&step, _)| step)
+            .collect::<BTreeSet<_>>();
+
+        let mut order = Vec::new();
+        while let Some(&step) = ready.iter().next() {
+            ready.remove(step);
+            order.push(step);
+            for next in self.blocks(step) {
+                if let Some(n) = waiting_on.get_mut(next.as_str()) {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.insert(next);
+                    }
+                }
+            }
+        }
+        order
+    }
 }
 
 fn parse_input(input: &str) -> Result<Project> {
-    let mut blocks = HashMap::new();
-    let mut blocked_by = HashMap::new();
-
-    for l in input.trim().lines() {
-        let caps = require_with!(
-            re(r"Step (.) must be finished before step (.) can begin.")?.captures(l),
-            "couldn't parse step {}",
-            l,
-        );
-
-        let blocking = parse(&caps[1])?;
-        let blocked = parse(&caps[2])?;
-
-        blocks
-            .entry(blocking)
-            .or_insert_with(HashSet::new)
-            .insert(blocked);
-
-        blocked_by
-            .entry(blocked)
-            .or_insert_with(HashSet::new)
-            .insert(blocking);
-    }
-
-    let ready = blocks
-        .keys()
-        .cloned()
-        .collect::<HashSet<_>>()
-        .difference(&blocked_by.keys().cloned().collect())
-        .cloned()
-        .collect();
-
-    Ok(Project {
-        ready,
-        blocks,
-        blocked_by,
-    })
+    let order = re(r"^Step (\S+) must be finished before step (\S+) can begin\.$")?;
+    let duration = re(r"^Step (\S+) takes (\d+) seconds?\.$")?;
+
+    let mut project = Project {
+        steps: BTreeSet::new(),
+        blocks: HashMap::new(),
+        blocked_by: HashMap::new(),
+        durations: HashMap::new(),
+    };
+
+    for l in input.trim().lines().map(str::trim) {
+        if let Some(caps) = duration.captures(l) {
+            let step = caps[1].to_string();
+            if project
+                .durations
+                .insert(step.clone(), parse(&caps[2])?)
+                .is_some()
+            {
+                bail!("duplicate instruction: {}", l);
+            }
+            project.steps.insert(step);
+            continue;
+        }
+
+        let caps = require_with!(order.captures(l), "couldn't parse step {}", l);
+        let blocking = caps[1].to_string();
+        let blocked = caps[2].to_string();
+
+        if !project
+            .blocks
+            .entry(blocking.clone())
+            .or_default()
+            .insert(blocked.clone())
+        {
+            bail!("duplicate instruction: {}", l);
+        }
+
+        project
+            .blocked_by
+            .entry(blocked.clone())
+            .or_default()
+            .insert(blocking.clone());
+
+        project.steps.insert(blocking);
+        project.steps.insert(blocked);
+    }
+
+    if let Some(cycle) = find_cycle(&project) {
+        bail!(
+            "steps depend on each other in a cycle: {}",
+            cycle.join(" -> ")
+        );
+    }
+
+    Ok(project)
+}
+
+// A path of steps, each blocking the next, which ends where it started. Every step left out of
+// the topological order is blocked by another step left out, so following those back from any of
+// them has to come round to a step already passed.
+fn find_cycle(project: &Project) -> Option<Vec<&str>> {
+    let sorted = project
+        .topological_order()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let stuck = |step: &&str| !sorted.contains(step);
+
+    let mut step = project.steps.iter().map(String::as_str).find(stuck)?;
+    let mut path = Vec::new();
+    let mut passed = HashMap::new();
+    while !passed.contains_key(step) {
+        passed.insert(step, path.len());
+        path.push(step);
+        step = project.blocking(step).map(String::as_str).find(stuck)?;
+    }
+
+    let mut cycle = path.split_off(passed[step]);
+    cycle.push(step);
+    cycle.reverse();
+    Some(cycle)
 }
 
 #[cfg(test)]
@@ -139,4 +494,224 @@ mod tests {
     fn sample_part2() {
         assert_eq!(part2(SAMPLE, 2, 0), Ok(15));
     }
+
+    #[test]
+    fn sample_table() {
+        assert_eq!(
+            schedule(SAMPLE, 2, 0).unwrap().table().unwrap(),
+            "\
+Second   Worker 1   Worker 2   Done
+   0        C          .
+   1        C          .
+   2        C          .
+   3        A          F       C
+   4        B          F       CA
+   5        B          F       CA
+   6        D          F       CAB
+   7        D          F       CAB
+   8        D          F       CAB
+   9        D          .       CABF
+  10        E          .       CABFD
+  11        E          .       CABFD
+  12        E          .       CABFD
+  13        E          .       CABFD
+  14        E          .       CABFD
+  15        .          .       CABFDE
+"
+        );
+    }
+
+    #[test]
+    fn sample_gantt() {
+        let schedule = schedule(SAMPLE, 2, 0).unwrap();
+
+        assert_eq!(
+            schedule.gantt().unwrap(),
+            "Worker 1   CCCABBDDDDEEEEE\nWorker 2   ...FFFFFF......\n"
+        );
+        assert_eq!(
+            schedule.tasks[2],
+            Task {
+                worker: 1,
+                step: "F".to_string(),
+                start: 3,
+                end: 9
+            }
+        );
+        assert!(part2(SAMPLE, 0, 0).is_err());
+    }
+
+    #[test]
+    fn named_steps() {
+        let input = "
+            Step design takes 10 seconds.
+            Step build takes 25 seconds.
+            Step test takes 5 seconds.
+            Step docs takes 1 second.
+            Step design must be finished before step build can begin.
+            Step design must be finished before step docs can begin.
+            Step build must be finished before step test can begin.
+        ";
+        let schedule = schedule(input, 2, 60).unwrap();
+
+        assert_eq!(
+            schedule
+                .tasks
+                .iter()
+                .map(|task| task.step.as_str())
+                .collect::<Vec<_>>(),
+            vec!["design", "build", "docs", "test"]
+        );
+        assert_eq!(schedule.makespan(), 40);
+        assert_eq!(
+            schedule.gantt().unwrap(),
+            "Worker 1   design----build--------------------test-\n\
+             Worker 2   ..........d.............................\n"
+        );
+        assert!(schedule.table().unwrap().starts_with(
+            "Second     Worker 1     Worker 2   Done\n   \
+             0        design       .\n"
+        ));
+
+        assert_eq!(part1(input), Ok("design,build,docs,test".to_string()));
+        assert_eq!(
+            part1("Step A must be finished before step long can begin."),
+            Ok("A,long".to_string())
+        );
+        assert_eq!(
+            part2("Step A must be finished before step long can begin.", 2, 0)
+                .unwrap_err()
+                .to_string(),
+            "no time given for step long"
+        );
+    }
+
+    #[test]
+    fn long_steps() {
+        // Ticking through every second would take a while.
+        let input = "
+            Step A must be finished before step B can begin.
+            Step B takes 4000000000 seconds.
+        ";
+        assert_eq!(part2(input, 1, 1_000), Ok(4_000_001_001));
+        assert_eq!(
+            schedule(input, 1, 1_000)
+                .unwrap()
+                .gantt()
+                .unwrap_err()
+                .to_string(),
+            "4000001001 seconds is too long to draw a second at a time"
+        );
+
+        let error =
+            |input: &str, base_seconds| part2(input, 1, base_seconds).unwrap_err().to_string();
+        assert_eq!(
+            error(
+                "Step A takes 4294967295 seconds.
+                 Step A must be finished before step B can begin.",
+                0
+            ),
+            "step B finishes too late to count"
+        );
+        assert_eq!(
+            error("Step A must be finished before step B can begin.", u32::MAX),
+            "step A takes too long to count"
+        );
+    }
+
+    #[test]
+    fn sample_analysis() {
+        let analysis = analyse(SAMPLE, 4, 0).unwrap();
+
+        assert_eq!(analysis.critical_path, vec!["C", "F", "E"]);
+        assert_eq!(analysis.critical_time, 14);
+        assert_eq!(analysis.min_workers, 3);
+        assert_eq!(analysis.makespans, vec![21, 15, 14, 14]);
+        assert_eq!(
+            analysis.to_string(),
+            "\
+Critical path: C -> F -> E (14s)
+Fewest workers to match it: 3
+Workers   Time
+      1     21
+      2     15
+      3     14
+      4     14
+"
+        );
+
+        // Beyond the curve asked for.
+        assert_eq!(analyse(SAMPLE, 1, 0).unwrap().min_workers, 3);
+        assert_eq!(
+            analyse(SAMPLE, 1, 60).unwrap().critical_path,
+            vec!["C", "A", "D", "E"]
+        );
+    }
+
+    #[test]
+    fn invalid_projects() {
+        let error = |input: &str| part1(input).unwrap_err().to_string();
+
+        assert_eq!(
+            error(
+                "Step A must be finished before step B can begin.
+                 Step B must be finished before step C can begin.
+                 Step C must be finished before step D can begin.
+                 Step D must be finished before step B can begin."
+            ),
+            "steps depend on each other in a cycle: B -> C -> D -> B"
+        );
+        assert_eq!(
+            error("Step A must be finished before step A can begin."),
+            "steps depend on each other in a cycle: A -> A"
+        );
+
+        // Far longer than the stack would allow a recursive search to follow.
+        let chain = (0..100_000)
+            .map(|i| {
+                format!(
+                    "Step s{} must be finished before step s{} can begin.\n",
+                    i,
+                    i + 1
+                )
+            })
+            .collect::<String>();
+        assert!(parse_input(&chain).is_ok());
+        let cycle = error(&format!(
+            "{}Step s100000 must be finished before step s0 can begin.",
+            chain
+        ));
+        assert!(cycle.starts_with("steps depend on each other in a cycle: s0 -> s1 -> s2 -> "));
+        assert!(cycle.ends_with(" -> s99999 -> s100000 -> s0"));
+
+        assert_eq!(
+            error(
+                "Step A must be finished before step B can begin.
+                 Step A must be finished before step B can begin."
+            ),
+            "duplicate instruction: Step A must be finished before step B can begin."
+        );
+        assert_eq!(
+            error(
+                "Step A takes 3 seconds.
+                 Step A takes 4 seconds."
+            ),
+            "duplicate instruction: Step A takes 4 seconds."
+        );
+    }
+
+    #[test]
+    fn blocked_steps() {
+        let mut project = parse_input(SAMPLE).unwrap();
+        project
+            .blocked_by
+            .entry("C".to_string())
+            .or_default()
+            .insert("Z".to_string());
+
+        assert_eq!(
+            work(&project, 2, 0).unwrap_err().to_string(),
+            "every worker is idle but ABCDEF can't start"
+        );
+    }
 }
@@ -1,43 +1,115 @@
-use crate::error::{parse, require_with, Result};
+use crate::error::{bail, parse, Result};
+
+// The nodes of the tree in the order they appear in the licence, so each node comes before its
+// children. Storing them flat rather than nested means no part of building, evaluating or
+// dropping the tree needs to recurse, however deep it goes.
+struct Tree {
+    nodes: Vec<Node>,
+}
 
 struct Node {
-    children: Vec<Node>,
+    children: Vec<usize>,
     metadata: Vec<i32>,
 }
 
-impl Node {
-    fn from_iterator(ns: &mut impl Iterator<Item = i32>) -> Result<Node> {
-        let no_of_children = require_with!(ns.next(), "couldn't create node");
-        let no_of_metadata = require_with!(ns.next(), "couldn't create node");
-        let children = (0..no_of_children)
-            .map(|_| Node::from_iterator(ns))
-            .collect::<Result<_>>()?;
-        let metadata = ns.take(no_of_metadata as usize).collect();
-        Ok(Node { children, metadata })
+// A node which is still being read.
+struct Partial {
+    node: usize,
+    children_left: i32,
+    metadata: i32,
+}
+
+impl Tree {
+    fn from_iterator(mut ns: impl Iterator<Item = i32>) -> Result<Tree> {
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut stack: Vec<Partial> = Vec::new();
+
+        loop {
+            // Read the header of the next node, and then the nodes and metadata it finishes.
+            let node = nodes.len();
+            if let Some(parent) = stack.last() {
+                nodes[parent.node].children.push(node);
+            }
+            let children_left = next_count(&mut ns, "child count")?;
+            let metadata = next_count(&mut ns, "metadata count")?;
+            nodes.push(Node {
+                children: Vec::new(),
+                metadata: Vec::new(),
+            });
+            stack.push(Partial {
+                node,
+                children_left,
+                metadata,
+            });
+
+            while let Some(partial) = stack.last_mut() {
+                if partial.children_left > 0 {
+                    partial.children_left -= 1;
+                    break;
+                }
+
+                for _ in 0..partial.metadata {
+                    let entry = next(&mut ns, "metadata entry")?;
+                    nodes[partial.node].metadata.push(entry);
+                }
+                stack.pop();
+            }
+
+            if stack.is_empty() {
+                break;
+            }
+        }
+
+        let left_over = ns.count();
+        if left_over > 0 {
+            bail!("{} numbers left over after the tree", left_over);
+        }
+
+        Ok(Tree { nodes })
     }
 
     fn sum_metadata(&self) -> i32 {
-        self.metadata.iter().sum::<i32>()
-            + self
-                .children
-                .iter()
-                .map(|child| child.sum_metadata())
-                .sum::<i32>()
+        self.nodes
+            .iter()
+            .map(|node| node.metadata.iter().sum::<i32>())
+            .sum()
     }
 
     fn value(&self) -> i32 {
-        if self.children.is_empty() {
-            self.metadata.iter().sum()
-        } else {
-            self.metadata
-                .iter()
-                .filter_map(|i| self.children.get((i - 1) as usize))
-                .map(|child| child.value())
-                .sum::<i32>()
+        // Children come after their parents, so working backwards every child's value is known
+        // by the time its parent needs it.
+        let mut values = vec![0; self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate().rev() {
+            values[i] = if node.children.is_empty() {
+                node.metadata.iter().sum()
+            } else {
+                node.metadata
+                    .iter()
+                    .filter_map(|&m| node.children.get((m as usize).wrapping_sub(1)))
+                    .map(|&child| values[child])
+                    .sum()
+            };
         }
+        values.first().cloned().unwrap_or(0)
+    }
+}
+
+fn next(ns: &mut impl Iterator<Item = i32>, what: &str) -> Result<i32> {
+    match ns.next() {
+        Some(n) => Ok(n),
+        None => bail!("licence ended early, expecting a {}", what),
     }
 }
 
+// As next, for a number of children or metadata entries, which can't be negative.
+fn next_count(ns: &mut impl Iterator<Item = i32>, what: &str) -> Result<i32> {
+    let n = next(ns, what)?;
+    if n < 0 {
+        bail!("negative {}: {}", what, n);
+    }
+    Ok(n)
+}
+
 pub fn part1(input: &str) -> Result<i32> {
     Ok(parse_input(input)?.sum_metadata())
 }
@@ -46,14 +118,13 @@ pub fn part2(input: &str) -> Result<i32> {
     Ok(parse_input(input)?.value())
 }
 
-fn parse_input(input: &str) -> Result<Node> {
+fn parse_input(input: &str) -> Result<Tree> {
     let ns: Vec<i32> = input
-        .trim()
-        .split(' ')
+        .split_whitespace()
         .map(|w| parse(w))
         .collect::<Result<_>>()?;
 
-    Node::from_iterator(&mut ns.into_iter())
+    Tree::from_iterator(ns.into_iter())
 }
 
 #[cfg(test)]
@@ -69,4 +140,41 @@ mod tests {
     fn sample_part2() {
         assert_eq!(part2("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2"), Ok(66));
     }
+
+    #[test]
+    fn deep_tree() {
+        // A chain of nodes each with one child and one metadata entry referring to it, ending in a
+        // leaf worth 5.
+        let depth = 1_000_000;
+        let licence = format!("{}0 1 5{}", "1 1 ".repeat(depth), " 1".repeat(depth));
+
+        assert_eq!(part1(&licence), Ok(5 + depth as i32));
+        assert_eq!(part2(&licence), Ok(5));
+    }
+
+    #[test]
+    fn invalid_licences() {
+        let error = |licence: &str| part1(licence).unwrap_err().to_string();
+
+        assert_eq!(
+            error("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1"),
+            "licence ended early, expecting a metadata entry"
+        );
+        assert_eq!(
+            error("2 3 0 3 10 11 12 1"),
+            "licence ended early, expecting a metadata count"
+        );
+        assert_eq!(error(""), "licence ended early, expecting a child count");
+        assert_eq!(
+            error("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2 7 8"),
+            "2 numbers left over after the tree"
+        );
+        assert_eq!(error("1 -1 0 0"), "negative metadata count: -1");
+    }
+
+    #[test]
+    fn metadata_out_of_range() {
+        assert_eq!(part2("2 3 0 1 7 0 1 8 0 3 -1"), Ok(0));
+        assert_eq!(part2("2 2 0 1 7 0 1 8 2 9"), Ok(8));
+    }
 }
@@ -1,92 +1,166 @@
-use crate::de_list::DeList;
-use crate::error::Result;
-use std::collections::HashMap;
-use std::rc::Rc;
+use crate::circle::Circle;
+use crate::error::{bail, parse, re, require_with, try_with, Result};
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
 
 type Score = i64;
 type Elf = i32;
 
 #[derive(Debug)]
-struct Ring(Rc<DeList<Score>>);
-
-impl Ring {
-    fn new() -> Self {
-        let root = Rc::new(DeList::new(0));
-        root.set_left(&root);
-        root.set_right(&root);
-        Ring(root)
-    }
-
-    fn insert(&mut self, value: Score) {
-        let new = Rc::new(DeList::new(value));
-        let left = self.0.left();
+struct Game {
+    marbles: Circle<Score>,
+    scores: HashMap<Elf, Score>,
+}
 
-        new.set_left(&left);
-        left.set_right(&new);
+impl Game {
+    fn new(last_marble: Score) -> Result<Self> {
+        let capacity = try_with!(
+            usize::try_from(last_marble),
+            "no game ends with marble {}",
+            last_marble
+        ) + 1;
+        let mut marbles = Circle::with_capacity(capacity);
+        marbles.insert(0);
 
-        new.set_right(&self.0);
-        self.0.set_left(&new);
+        Ok(Game {
+            marbles,
+            scores: HashMap::new(),
+        })
+    }
 
-        self.rotate_left(1);
+    fn play_turn(&mut self, marble: Score, elf: Elf) {
+        if marble % 23 == 0 {
+            self.marbles.rotate(-7);
+            *self.scores.entry(elf).or_insert(0) += marble + self.marbles.remove().unwrap_or(0);
+        } else {
+            self.marbles.rotate(1);
+            self.marbles.insert(marble);
+        }
     }
 
-    fn remove(&mut self) -> Score {
-        let left = self.0.left();
-        let right = self.0.right();
+    fn high_score(&self) -> Score {
+        self.scores.values().cloned().max().unwrap_or(0)
+    }
 
-        let value = *self.0.value();
+    // The marbles clockwise from marble 0 (or the lowest remaining marble, should 0 be removed).
+    fn circle(&self) -> Vec<Score> {
+        let mut circle = self.marbles.iter().cloned().collect::<Vec<_>>();
+        if let Some(start) = (0..circle.len()).min_by_key(|&i| circle[i]) {
+            circle.rotate_left(start);
+        }
+        circle
+    }
+}
 
-        left.set_right(&right);
-        right.set_left(&left);
+// Traces are for checking against the worked example, so are limited to games of this size.
+const TRACE_LIMIT: Score = 10_000;
 
-        *self = Ring(right);
+// The state of the game after an elf's turn.
+#[derive(Debug, PartialEq)]
+pub struct Turn {
+    pub elf: Elf,
+    pub current: Score,
+    pub circle: Vec<Score>,
+}
 
-        value
-    }
+// Renders as in the puzzle text, numbering elves from 1: `[3]  0 16  8 17 ( 4) 18`.
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .circle
+            .iter()
+            .map(|m| m.to_string().len())
+            .max()
+            .unwrap_or(0);
 
-    fn rotate_left(&mut self, n: usize) {
-        for _ in 0..n {
-            *self = Ring(self.0.left());
+        write!(f, "[{}]", self.elf + 1)?;
+        for marble in self.circle.iter() {
+            if *marble == self.current {
+                write!(f, " ({:>width$})", marble, width = width)?;
+            } else {
+                write!(f, " {:>width$}", marble, width = width)?;
+            }
         }
+        Ok(())
     }
+}
 
-    fn rotate_right(&mut self, n: usize) {
-        for _ in 0..n {
-            *self = Ring(self.0.right());
-        }
+pub struct Trace {
+    game: Game,
+    players: Elf,
+    turns: Box<dyn Iterator<Item = (Score, Elf)>>,
+}
+
+impl Trace {
+    // Every elf's score so far, indexed by elf.
+    pub fn scores(&self) -> Vec<Score> {
+        (0..self.players)
+            .map(|elf| self.game.scores.get(&elf).cloned().unwrap_or(0))
+            .collect()
     }
 }
-#[derive(Debug)]
-struct Game {
-    marbles: Ring,
-    scores: HashMap<Elf, Score>,
+
+impl Iterator for Trace {
+    type Item = Turn;
+
+    fn next(&mut self) -> Option<Turn> {
+        let (marble, elf) = self.turns.next()?;
+        self.game.play_turn(marble, elf);
+
+        Some(Turn {
+            elf,
+            current: *self.game.marbles.current()?,
+            circle: self.game.circle(),
+        })
+    }
 }
 
-impl Game {
-    fn new() -> Self {
-        Game {
-            marbles: Ring::new(),
-            scores: HashMap::new(),
-        }
+pub fn trace(players: Elf, last_marble: Score) -> Result<Trace> {
+    if players < 1 {
+        bail!("need at least one player");
+    }
+    if last_marble < 0 {
+        bail!("the last marble can't be negative");
+    }
+    if last_marble > TRACE_LIMIT {
+        bail!("can only trace games of up to {} marbles", TRACE_LIMIT);
     }
 
-    fn play_turn(&mut self, marble: Score, elf: Elf) {
-        if marble % 23 == 0 {
-            self.marbles.rotate_left(7);
-            *self.scores.entry(elf).or_insert(0) += marble + self.marbles.remove();
-        } else {
-            self.marbles.rotate_right(2);
-            self.marbles.insert(marble);
-        }
+    Ok(Trace {
+        game: Game::new(last_marble)?,
+        players,
+        turns: Box::new((1..=last_marble).zip((0..players).cycle())),
+    })
+}
+
+// Every turn of the game, followed by a table of the final scores.
+pub fn report(players: Elf, last_marble: Score) -> Result<String> {
+    let mut trace = trace(players, last_marble)?;
+    let mut s = "[-] (0)\n".to_string();
+
+    for turn in trace.by_ref() {
+        s.push_str(&format!("{}\n", turn));
     }
 
-    fn high_score(&self) -> Score {
-        self.scores.values().cloned().max().unwrap_or(0)
+    let scores = trace.scores();
+    let high_score = scores.iter().cloned().max().unwrap_or(0);
+
+    s.push_str("\nElf  Score\n");
+    for (elf, score) in scores.iter().enumerate() {
+        s.push_str(&format!(
+            "{:>3}  {:>5}{}\n",
+            elf + 1,
+            score,
+            if *score == high_score { "  *" } else { "" }
+        ));
     }
+
+    Ok(s)
 }
 
 pub fn part1(players: Elf, last_marble: Score) -> Result<Score> {
-    let mut game = Game::new();
+    let mut game = Game::new(last_marble)?;
     for (marble, elf) in (1..=last_marble).zip((0..players).cycle()) {
         game.play_turn(marble, elf);
     }
@@ -94,11 +168,90 @@ pub fn part1(players: Elf, last_marble: Score) -> Result<Score> {
 }
 
 pub fn part2(players: Elf, last_marble: Score) -> Result<Score> {
-    let mut game = Game::new();
-    for (marble, elf) in (1..=last_marble * 100).zip((0..players).cycle()) {
-        game.play_turn(marble, elf);
+    high_score_by_blocks(players, last_marble * 100)
+}
+
+// The same high score as simulating the game turn by turn, but worked out a block of 23 marbles
+// at a time, and without storing the marbles that will never be revisited.
+//
+// Keep the circle as a queue running clockwise from the marble after the current one, so that the
+// current marble is at the back. If the block before marble 23k + 23 starts with y1, ..., y22 at
+// the front of the queue, then its first 22 turns move each yi to the back followed by marble
+// 23k + i, and the 23rd turn removes y19 and leaves the queue as
+//
+//     y20 m20 y21 m21 y22 m22 (rest of the queue) y1 m1 ... y18 m18 m19
+//
+// So each block scores 23k + 23 + y19, and shifts the rest of the queue 16 places forwards. A
+// marble which is further back than the remaining blocks can shift it is never read again, so
+// once the queue is that long the tails stop being stored, which happens less than halfway in.
+fn high_score_by_blocks(players: Elf, last_marble: Score) -> Result<Score> {
+    if players < 1 {
+        bail!("need at least one player");
     }
-    Ok(game.high_score())
+
+    let blocks = last_marble / 23;
+    if blocks == 0 {
+        return Ok(0);
+    }
+
+    let mut scores = vec![0; players as usize];
+    let mut award = |marble: Score, y19: Score| {
+        scores[((marble - 1) % Score::from(players)) as usize] += marble + y19;
+    };
+
+    // The first block starts with fewer than 22 marbles in the queue, so play it out in full.
+    let mut queue = VecDeque::from(vec![0]);
+    for marble in 1..23 {
+        queue.rotate_left(1);
+        queue.push_back(marble);
+    }
+    queue.rotate_right(7);
+    award(23, queue.pop_back().unwrap_or(0));
+    queue.rotate_left(1);
+
+    let mut y = [0; 22];
+    for k in 1..blocks {
+        let base = 23 * k;
+        for yi in y.iter_mut() {
+            *yi = require_with!(queue.pop_front(), "ran out of marbles");
+        }
+
+        award(base + 23, y[18]);
+
+        for i in (19..22).rev() {
+            queue.push_front(base + i as Score + 1);
+            queue.push_front(y[i]);
+        }
+
+        let remaining = blocks - k - 1;
+        let limit = if remaining == 0 {
+            0
+        } else {
+            16 * (remaining as usize - 1) + 22
+        };
+        for (i, &yi) in y.iter().take(18).enumerate() {
+            if queue.len() >= limit {
+                break;
+            }
+            queue.push_back(yi);
+            queue.push_back(base + i as Score + 1);
+        }
+        if queue.len() < limit {
+            queue.push_back(base + 19);
+        }
+    }
+
+    Ok(scores.into_iter().max().unwrap_or(0))
+}
+
+pub fn parse_input(input: &str) -> Result<(Elf, Score)> {
+    let caps = require_with!(
+        re(r"(\d+) players; last marble is worth (\d+) points")?.captures(input),
+        "couldn't parse game {}",
+        input.trim(),
+    );
+
+    Ok((parse(&caps[1])?, parse(&caps[2])?))
 }
 
 #[cfg(test)]
@@ -113,5 +266,68 @@ mod tests {
         assert_eq!(part1(17, 1104), Ok(2764));
         assert_eq!(part1(21, 6111), Ok(54718));
         assert_eq!(part1(30, 5807), Ok(37305));
+        assert!(part1(9, -1).is_err());
+    }
+
+    #[test]
+    fn blocks_match_simulation() {
+        assert_eq!(high_score_by_blocks(9, 25), Ok(32));
+        assert_eq!(high_score_by_blocks(10, 1618), Ok(8317));
+        assert_eq!(high_score_by_blocks(13, 7999), Ok(146373));
+        assert_eq!(high_score_by_blocks(17, 1104), Ok(2764));
+        assert_eq!(high_score_by_blocks(21, 6111), Ok(54718));
+        assert_eq!(high_score_by_blocks(30, 5807), Ok(37305));
+
+        for players in 1..12 {
+            for last_marble in (0..600).step_by(37) {
+                assert_eq!(
+                    high_score_by_blocks(players, last_marble),
+                    part1(players, last_marble)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn blocks_match_real_input() {
+        let (players, last_marble) = parse_input(include_str!("input/day09")).unwrap();
+        let high_score = part1(players, last_marble * 100).unwrap();
+
+        assert_eq!(high_score, 3_133_277_384);
+        assert_eq!(part2(players, last_marble), Ok(high_score));
+    }
+
+    #[test]
+    fn sample_trace() {
+        let turns = trace(9, 25)
+            .unwrap()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(turns[0], "[1] 0 (1)");
+        assert_eq!(turns[3], "[4] 0 (4) 2 1 3");
+        assert_eq!(
+            turns[21],
+            "[4]  0 16  8 17  4 18  9 19  2 20 10 21  5 (22) 11  1 12  6 13  3 14  7 15"
+        );
+        assert_eq!(
+            turns[22],
+            "[5]  0 16  8 17  4 18 (19)  2 20 10 21  5 22 11  1 12  6 13  3 14  7 15"
+        );
+        assert_eq!(
+            turns[24],
+            "[7]  0 16  8 17  4 18 19  2 24 20 (25) 10 21  5 22 11  1 12  6 13  3 14  7 15"
+        );
+    }
+
+    #[test]
+    fn sample_scores() {
+        let mut game = trace(9, 25).unwrap();
+        game.by_ref().for_each(drop);
+
+        assert_eq!(game.scores(), vec![0, 0, 0, 0, 32, 0, 0, 0, 0]);
+        assert!(report(9, 25).unwrap().contains("  5     32  *\n"));
+        assert!(trace(9, TRACE_LIMIT + 1).is_err());
+        assert!(trace(9, -5).is_err());
     }
 }
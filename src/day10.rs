@@ -1,4 +1,5 @@
 use crate::error::{parse, re, require_with, Error, Result};
+use crate::grid::Grid;
 use crate::point::Point;
 use std::str::FromStr;
 
@@ -53,19 +54,17 @@ impl ToString for Sky {
             return format!("({}x{})", max_x - min_x, max_y - min_y);
         }
 
-        let mut s = "\n".to_string();
-
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                if self.lights.iter().any(|l| l.position == Point { x, y }) {
-                    s.push('#');
-                } else {
-                    s.push('.');
-                }
-            }
-            s.push('\n');
+        let mut grid = Grid::new(
+            Point { x: min_x, y: min_y },
+            Point { x: max_x, y: max_y },
+            false,
+        );
+        for light in self.lights.iter() {
+            grid[light.position] = true;
         }
 
+        let mut s = "\n".to_string();
+        s.push_str(&grid.render(|&lit| if lit { '#' } else { '.' }));
         s
     }
 }
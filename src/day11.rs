@@ -1,12 +1,14 @@
 use crate::error::Result;
+use crate::grid::Grid;
+use crate::point::Point;
 use std::cmp::max;
 
 pub fn part1(serial: i32) -> Result<String> {
-    let (x, y) = (1..=300usize)
-        .flat_map(|x| (1..=300usize).map(move |y| (x, y)))
+    let (x, y) = (1..=300)
+        .flat_map(|x| (1..=300).map(move |y| (x, y)))
         .max_by_key(|(x, y)| {
             (0..3)
-                .flat_map(|i| (0..3usize).map(move |j| power(x + i, y + j, serial)))
+                .flat_map(|i| (0..3).map(move |j| power(x + i, y + j, serial)))
                 .sum::<i32>()
         })
         .unwrap();
@@ -15,27 +17,29 @@ pub fn part1(serial: i32) -> Result<String> {
 
 pub fn part2(serial: i32) -> Result<String> {
     // https://en.wikipedia.org/wiki/Summed-area_table
-    let mut sat = vec![vec![0; 301]; 301];
+    let mut sat = Grid::new(Point { x: 0, y: 0 }, Point { x: 300, y: 300 }, 0);
     for (x, y) in (1..=300).flat_map(|x| (1..=300).map(move |y| (x, y))) {
-        sat[x][y] = power(x, y, serial) + sat[x][y - 1] + sat[x - 1][y] - sat[x - 1][y - 1];
+        sat[Point { x, y }] =
+            power(x, y, serial) + sat[Point { x, y: y - 1 }] + sat[Point { x: x - 1, y }]
+                - sat[Point { x: x - 1, y: y - 1 }];
     }
 
-    let (x, y, size) = (1..=300usize)
-        .flat_map(|x| (1..=300usize).map(move |y| (x, y)))
-        .flat_map(|(x, y)| (1..=301usize - max(x, y)).map(move |size| (x, y, size)))
-        .max_by_key(|(x, y, size)| {
-            sat[x + size - 1][y + size - 1] + sat[x - 1][y - 1]
-                - sat[x - 1][y + size - 1]
-                - sat[x + size - 1][y - 1]
+    let sat = |x, y| sat[Point { x, y }];
+
+    let (x, y, size) = (1..=300)
+        .flat_map(|x| (1..=300).map(move |y| (x, y)))
+        .flat_map(|(x, y)| (1..=301 - max(x, y)).map(move |size| (x, y, size)))
+        .max_by_key(|&(x, y, size)| {
+            sat(x + size - 1, y + size - 1) + sat(x - 1, y - 1)
+                - sat(x - 1, y + size - 1)
+                - sat(x + size - 1, y - 1)
         })
         .unwrap();
 
     Ok(format!("{},{},{}", x, y, size))
 }
 
-fn power(x: usize, y: usize, serial: i32) -> i32 {
-    let x = x as i32;
-    let y = y as i32;
+fn power(x: i32, y: i32, serial: i32) -> i32 {
     ((x + 10) * y + serial) * (x + 10) / 100 % 10 - 5
 }
 
@@ -1,107 +1,83 @@
 use crate::error::Result;
+use crate::grid::Grid;
+use crate::point::Point;
 use std::cell::RefCell;
-use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 struct Cart {
-    pos: (usize, usize),
-    dir: char,
+    pos: Point,
+    dir: Point,
     turns: usize,
     crashed: bool,
 }
 
 impl Cart {
     fn tick(&mut self) {
-        match self.dir {
-            '^' => self.pos.1 -= 1,
-            '>' => self.pos.0 += 1,
-            'v' => self.pos.1 += 1,
-            '<' => self.pos.0 -= 1,
-            _ => unreachable!(),
-        }
+        self.pos += self.dir;
     }
 
     fn turn(&mut self, c: char) {
         match c {
-            '/' => match self.dir {
-                '^' => self.dir = '>',
-                '>' => self.dir = '^',
-                'v' => self.dir = '<',
-                '<' => self.dir = 'v',
-                _ => unreachable!(),
-            },
-            '\\' => match self.dir {
-                '^' => self.dir = '<',
-                '>' => self.dir = 'v',
-                'v' => self.dir = '>',
-                '<' => self.dir = '^',
-                _ => unreachable!(),
-            },
+            '/' => {
+                self.dir = Point {
+                    x: -self.dir.y,
+                    y: -self.dir.x,
+                }
+            }
+            '\\' => {
+                self.dir = Point {
+                    x: self.dir.y,
+                    y: self.dir.x,
+                }
+            }
             '+' => {
                 match self.turns % 3 {
-                    0 => match self.dir {
-                        '^' => self.dir = '<',
-                        '>' => self.dir = '^',
-                        'v' => self.dir = '>',
-                        '<' => self.dir = 'v',
-                        _ => unreachable!(),
-                    },
-                    2 => match self.dir {
-                        '^' => self.dir = '>',
-                        '>' => self.dir = 'v',
-                        'v' => self.dir = '<',
-                        '<' => self.dir = '^',
-                        _ => unreachable!(),
-                    },
+                    0 => self.dir = self.dir.turn_left(),
+                    2 => self.dir = self.dir.turn_right(),
                     _ => {}
                 }
                 self.turns += 1;
             }
-            _ => unreachable!(),
+            _ => {}
         }
     }
 }
 
 pub fn part1(input: &str) -> Result<String> {
-    let (mut carts, tracks) = parse_input(input);
+    let (mut carts, tracks) = parse_input(input)?;
 
     loop {
         sort_carts(&mut carts);
         for cart in carts.iter() {
             let pos = cart.borrow().pos;
-            if let Some(c) = tracks.get(&pos) {
-                cart.borrow_mut().turn(*c);
-            }
+            cart.borrow_mut().turn(tracks[pos]);
             cart.borrow_mut().tick();
-            if let Some((x, y)) = mark_crashed(&carts) {
-                return Ok(format!("{},{}", x, y));
+            if let Some(pos) = mark_crashed(&carts) {
+                return Ok(pos.to_string());
             }
         }
     }
 }
 
 pub fn part2(input: &str) -> Result<String> {
-    let (mut carts, tracks) = parse_input(input);
+    let (mut carts, tracks) = parse_input(input)?;
 
     loop {
         sort_carts(&mut carts);
         for cart in carts.iter() {
             let pos = cart.borrow().pos;
-            if let Some(c) = tracks.get(&pos) {
-                cart.borrow_mut().turn(*c);
-            }
+            cart.borrow_mut().turn(tracks[pos]);
             cart.borrow_mut().tick();
             mark_crashed(&carts);
         }
         carts.retain(|cart| !cart.borrow().crashed);
         if carts.len() == 1 {
-            let (x, y) = carts[0].borrow().pos;
-            return Ok(format!("{},{}", x, y));
+            return Ok(carts[0].borrow().pos.to_string());
         }
     }
 }
 
-fn mark_crashed(carts: &[RefCell<Cart>]) -> Option<(usize, usize)> {
+fn mark_crashed(carts: &[RefCell<Cart>]) -> Option<Point> {
     for i in 0..carts.len() {
         for j in 0..i {
             let mut cart_i = carts[i].borrow_mut();
@@ -116,40 +92,33 @@ fn mark_crashed(carts: &[RefCell<Cart>]) -> Option<(usize, usize)> {
     None
 }
 
-fn sort_carts(carts: &mut Vec<RefCell<Cart>>) {
-    carts.sort_unstable_by(|a, b| {
-        let a = a.borrow();
-        let b = b.borrow();
-        if a.pos.1 == b.pos.1 {
-            a.pos.0.cmp(&b.pos.0)
-        } else {
-            a.pos.1.cmp(&b.pos.1)
-        }
-    });
+fn sort_carts(carts: &mut [RefCell<Cart>]) {
+    carts.sort_unstable_by_key(|cart| cart.borrow().pos);
 }
 
-fn parse_input(input: &str) -> (Vec<RefCell<Cart>>, HashMap<(usize, usize), char>) {
-    let mut carts = Vec::new();
-    let mut tracks = HashMap::new();
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                '^' | '>' | 'v' | '<' => carts.push(RefCell::new(Cart {
-                    pos: (x, y),
-                    dir: c,
-                    turns: 0,
-                    crashed: false,
-                })),
-                '+' | '/' | '\\' => {
-                    tracks.insert((x, y), c);
-                }
-                _ => {}
-            }
-        }
-    }
-
-    (carts, tracks)
+fn parse_input(input: &str) -> Result<(Vec<RefCell<Cart>>, Grid<char>)> {
+    let tracks = Grid::parse(input, Ok)?;
+
+    let carts = tracks
+        .iter()
+        .filter_map(|(pos, &c)| {
+            let dir = match c {
+                '^' => Point::UP,
+                '>' => Point::RIGHT,
+                'v' => Point::DOWN,
+                '<' => Point::LEFT,
+                _ => return None,
+            };
+            Some(RefCell::new(Cart {
+                pos,
+                dir,
+                turns: 0,
+                crashed: false,
+            }))
+        })
+        .collect();
+
+    Ok((carts, tracks))
 }
 
 #[cfg(test)]
@@ -0,0 +1,255 @@
+use crate::error::Result;
+use crate::point::Point;
+use std::ops::{Index, IndexMut};
+
+// A dense rectangular grid covering every point from `min` to `max` inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    min: Point,
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const NEIGHBOURS_4: [Point; 4] = [Point::UP, Point::LEFT, Point::RIGHT, Point::DOWN];
+
+impl<T> Grid<T> {
+    pub fn new(min: Point, max: Point, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid::from_fn(min, max, |_| value.clone())
+    }
+
+    pub fn from_fn(min: Point, max: Point, mut f: impl FnMut(Point) -> T) -> Self {
+        let width = (max.x - min.x + 1).max(0) as usize;
+        let height = (max.y - min.y + 1).max(0) as usize;
+        let mut grid = Grid {
+            min,
+            width,
+            height,
+            cells: Vec::with_capacity(width * height),
+        };
+        for p in grid.points() {
+            grid.cells.push(f(p));
+        }
+        grid
+    }
+
+    // Parses a character map, with the top left character at the origin. Lines shorter than the
+    // longest line are padded with spaces.
+    pub fn parse(s: &str, mut f: impl FnMut(char) -> Result<T>) -> Result<Self> {
+        let lines = s.lines().collect::<Vec<_>>();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for l in lines.iter() {
+            let mut chars = l.chars();
+            for _ in 0..width {
+                cells.push(f(chars.next().unwrap_or(' '))?);
+            }
+        }
+
+        Ok(Grid {
+            min: Point::ORIGIN,
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    // Renders one line per row, each terminated by a newline.
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            s.extend(row.iter().map(&mut f));
+            s.push('\n');
+        }
+        s
+    }
+
+    // As render, but also passing each point.
+    pub fn render_points(&self, mut f: impl FnMut(Point, &T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for (p, v) in self.iter() {
+            s.push(f(p, v));
+            if p.x == self.min.x + self.width as i32 - 1 {
+                s.push('\n');
+            }
+        }
+        s
+    }
+
+    pub fn max(&self) -> Point {
+        self.min
+            + Point {
+                x: self.width as i32 - 1,
+                y: self.height as i32 - 1,
+            }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.offset(p).is_some()
+    }
+
+    // Whether p lies on the outermost rows or columns of the grid.
+    pub fn on_boundary(&self, p: Point) -> bool {
+        let max = self.max();
+        self.contains(p) && (p.x == self.min.x || p.x == max.x || p.y == self.min.y || p.y == max.y)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    // Every point in the grid in reading order: top to bottom, then left to right.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (min, width, height) = (self.min, self.width as i32, self.height as i32);
+        (0..height).flat_map(move |y| {
+            (0..width).map(move |x| Point {
+                x: min.x + x,
+                y: min.y + y,
+            })
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    // The orthogonal neighbours of p which lie in the grid, in reading order.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .map(move |&d| p + d)
+            .filter(move |&q| self.contains(q))
+    }
+
+    // The orthogonal and diagonal neighbours of p which lie in the grid, in reading order.
+    #[allow(dead_code)] // None of the puzzles so far look at diagonals; day 18's will.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        const NEIGHBOURS_8: [Point; 8] = [
+            Point { x: -1, y: -1 },
+            Point::UP,
+            Point { x: 1, y: -1 },
+            Point::LEFT,
+            Point::RIGHT,
+            Point { x: -1, y: 1 },
+            Point::DOWN,
+            Point { x: 1, y: 1 },
+        ];
+
+        NEIGHBOURS_8
+            .iter()
+            .map(move |&d| p + d)
+            .filter(move |&q| self.contains(q))
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        let x = p.x - self.min.x;
+        let y = p.y - self.min.y;
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(y as usize * self.width + x as usize)
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.offset(p) {
+            Some(i) => &self.cells[i],
+            None => panic!("({}, {}) is outside the grid", p.x, p.y),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        match self.offset(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("({}, {}) is outside the grid", p.x, p.y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::bail;
+
+    fn parse_bools(s: &str) -> Result<Grid<bool>> {
+        Grid::parse(s, |c| match c {
+            '#' => Ok(true),
+            '.' | ' ' => Ok(false),
+            _ => bail!("unexpected character {} in map", c),
+        })
+    }
+
+    const MAP: &'static str = "#..\n.#.\n..#\n#\n";
+
+    #[test]
+    fn parse_and_render() {
+        let grid = parse_bools(MAP).unwrap();
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 4);
+        assert_eq!(
+            grid.render(|&b| if b { '#' } else { '.' }),
+            "#..\n.#.\n..#\n#..\n"
+        );
+        assert!(parse_bools("#x").is_err());
+    }
+
+    #[test]
+    fn bounds() {
+        let grid = Grid::new(Point { x: -1, y: 2 }, Point { x: 1, y: 3 }, 0);
+
+        assert_eq!(grid.max(), Point { x: 1, y: 3 });
+        assert_eq!(grid.values().count(), 6);
+        assert!(grid.contains(Point { x: -1, y: 2 }));
+        assert!(!grid.contains(Point { x: 2, y: 2 }));
+        assert!(!grid.contains(Point { x: 0, y: 1 }));
+        assert!(grid.on_boundary(Point { x: 0, y: 3 }));
+        assert_eq!(grid.get(Point { x: 0, y: 4 }), None);
+    }
+
+    #[test]
+    fn reading_order() {
+        let grid = Grid::from_fn(Point::ORIGIN, Point { x: 1, y: 1 }, |p| p.x + 10 * p.y);
+
+        assert_eq!(
+            grid.values().cloned().collect::<Vec<_>>(),
+            vec![0, 1, 10, 11]
+        );
+        assert!(grid.iter().all(|(p, &v)| v == p.x + 10 * p.y));
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(Point::ORIGIN, Point { x: 2, y: 2 }, ());
+        let corner = Point::ORIGIN;
+        let centre = Point { x: 1, y: 1 };
+
+        assert_eq!(grid.neighbours4(corner).count(), 2);
+        assert_eq!(grid.neighbours8(corner).count(), 3);
+        assert_eq!(grid.neighbours4(centre).count(), 4);
+        assert_eq!(grid.neighbours8(centre).count(), 8);
+        assert_eq!(grid.neighbours4(centre).next(), Some(Point { x: 1, y: 0 }));
+    }
+}
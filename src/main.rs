@@ -1,5 +1,10 @@
-use std::{env, fmt, process};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::{env, fmt, fs, process};
 
+mod answers;
+mod circle;
+mod client;
 mod day01;
 mod day02;
 mod day03;
@@ -14,11 +19,15 @@ mod day11;
 mod day12;
 mod day13;
 mod day14;
-mod de_list;
 mod error;
+mod grid;
 mod point;
 
-use crate::error::{bail, try_with, Result};
+use crate::answers::{Answers, Verdict};
+use crate::client::Client;
+use crate::error::{bail, parse, try_with, Result};
+
+const DAYS: RangeInclusive<u8> = 1..=14;
 
 #[derive(Debug, PartialEq)]
 struct Answer {
@@ -35,67 +44,219 @@ impl Answer {
     }
 }
 
+fn input(day: u8) -> Result<&'static str> {
+    match day {
+        1 => Ok(include_str!("input/day01")),
+        2 => Ok(include_str!("input/day02")),
+        3 => Ok(include_str!("input/day03")),
+        4 => Ok(include_str!("input/day04")),
+        5 => Ok(include_str!("input/day05")),
+        6 => Ok(include_str!("input/day06")),
+        7 => Ok(include_str!("input/day07")),
+        8 => Ok(include_str!("input/day08")),
+        9 => Ok(include_str!("input/day09")),
+        10 => Ok(include_str!("input/day10")),
+        11 => Ok(include_str!("input/day11")),
+        12 => Ok(include_str!("input/day12")),
+        13 => Ok(include_str!("input/day13")),
+        14 => Ok(include_str!("input/day14")),
+        day => bail!("no input for day {}", day),
+    }
+}
+
 fn run_day(day: u8) -> Result<Answer> {
-    let input01 = include_str!("input/day01");
-    let input02 = include_str!("input/day02");
-    let input03 = include_str!("input/day03");
-    let input04 = include_str!("input/day04");
-    let input05 = include_str!("input/day05");
-    let input06 = include_str!("input/day06");
-    let input07 = include_str!("input/day07");
-    let input08 = include_str!("input/day08");
-    let input10 = include_str!("input/day10");
-    let input12 = include_str!("input/day12");
-    let input13 = include_str!("input/day13");
+    solve(day, input(day)?)
+}
 
+fn solve(day: u8, input: &str) -> Result<Answer> {
     match day {
-        1 => Ok(Answer::new(day01::part1(input01)?, day01::part2(input01)?)),
-        2 => Ok(Answer::new(day02::part1(input02)?, day02::part2(input02)?)),
-        3 => Ok(Answer::new(day03::part1(input03)?, day03::part2(input03)?)),
-        4 => Ok(Answer::new(day04::part1(input04)?, day04::part2(input04)?)),
-        5 => Ok(Answer::new(day05::part1(input05)?, day05::part2(input05)?)),
+        1 => Ok(Answer::new(day01::part1(input)?, day01::part2(input)?)),
+        2 => Ok(Answer::new(day02::part1(input)?, day02::part2(input)?)),
+        3 => Ok(Answer::new(day03::part1(input)?, day03::part2(input)?)),
+        4 => Ok(Answer::new(day04::part1(input)?, day04::part2(input)?)),
+        5 => Ok(Answer::new(day05::part1(input)?, day05::part2(input)?)),
         6 => Ok(Answer::new(
-            day06::part1(input06)?,
-            day06::part2(input06, 10000)?,
+            day06::part1(input)?,
+            day06::part2(input, 10000)?,
         )),
         7 => Ok(Answer::new(
-            day07::part1(input07)?,
-            day07::part2(input07, 5, 60)?,
+            day07::part1(input)?,
+            day07::part2(input, 5, 60)?,
         )),
-        8 => Ok(Answer::new(day08::part1(input08)?, day08::part2(input08)?)),
-        9 => Ok(Answer::new(
-            day09::part1(466, 71436)?,
-            day09::part2(466, 71436)?,
+        8 => Ok(Answer::new(day08::part1(input)?, day08::part2(input)?)),
+        9 => {
+            let (players, last_marble) = day09::parse_input(input)?;
+            Ok(Answer::new(
+                day09::part1(players, last_marble)?,
+                day09::part2(players, last_marble)?,
+            ))
+        }
+        10 => Ok(Answer::new(day10::part1(input, 10009)?, 10009)),
+        11 => {
+            let serial = parse(input.trim())?;
+            Ok(Answer::new(day11::part1(serial)?, day11::part2(serial)?))
+        }
+        12 => Ok(Answer::new(day12::part1(input)?, 3_350_000_000_000i64)),
+        13 => Ok(Answer::new(day13::part1(input)?, day13::part2(input)?)),
+        14 => Ok(Answer::new(
+            day14::part1(parse(input.trim())?)?,
+            day14::part2(input.trim())?,
         )),
-        10 => Ok(Answer::new(day10::part1(input10, 10009)?, 10009)),
-        11 => Ok(Answer::new(day11::part1(3613)?, day11::part2(3613)?)),
-        12 => Ok(Answer::new(day12::part1(input12)?, 3_350_000_000_000i64)),
-        13 => Ok(Answer::new(day13::part1(input13)?, day13::part2(input13)?)),
-        14 => Ok(Answer::new(day14::part1(409_551)?, day14::part2("409551")?)),
         day => bail!("day {} not yet implemented", day),
     }
 }
 
-fn run() -> Result<Answer> {
-    let args = env::args().skip(1).collect::<Vec<_>>();
+fn answers_path() -> PathBuf {
+    env::var_os("AOC_ANSWERS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("answers"))
+}
+
+fn parse_day(s: &str) -> Result<u8> {
+    Ok(try_with!(s.parse(), "failed to parse day {}", s))
+}
+
+fn print_answer(answer: &Answer) {
+    println!("part1: {}", answer.part1);
+    println!("part2: {}", answer.part2);
+}
+
+fn record(day: u8) -> Result<()> {
+    let path = answers_path();
+    let mut answers = Answers::load(&path)?;
+    let answer = run_day(day)?;
+
+    print_answer(&answer);
+    answers.record(day, &answer);
+    answers.save(&path)?;
+    println!("recorded day {} in {}", day, path.display());
+
+    Ok(())
+}
+
+fn fetch(day: u8) -> Result<()> {
+    print_answer(&solve(day, &Client::from_env().input(day)?)?);
+    Ok(())
+}
+
+// Day 10 part 1 is a drawing of letters to be read by eye, and day 10 part 2 and day 12 part 2
+// are hard-coded for one particular input, so none of them can be trusted for a fetched input.
+fn can_submit(day: u8, part: u8) -> bool {
+    !matches!((day, part), (10, _) | (12, 2))
+}
+
+fn submit(day: u8, part: u8) -> Result<()> {
+    if part != 1 && part != 2 {
+        bail!("no part {}", part);
+    }
+    if !can_submit(day, part) {
+        bail!("day {} part {} has to be solved by hand", day, part);
+    }
+
+    let client = Client::from_env();
+    let answer = solve(day, &client.input(day)?)?;
+    let answer = if part == 1 {
+        answer.part1
+    } else {
+        answer.part2
+    };
+    if answer.is_empty() || answer.contains(char::is_whitespace) {
+        bail!("not submitting {:?}, which isn't a single value", answer);
+    }
+
+    println!("{}", client.submit(day, part, &answer)?);
+    Ok(())
+}
+
+fn trace(day: u8, args: &[&str]) -> Result<()> {
+    match (day, args) {
+        (3, ["map"]) => print!("{}", day03::render(input(3)?)?),
+        (3, ["report"]) => print!("{}", day03::report(input(3)?)?),
+        (3, _) => bail!("usage: trace 3 map|report"),
+        (4, []) => print!("{}", day04::report(input(4)?)?),
+        (4, _) => bail!("usage: trace 4"),
+        (6, ["map"]) => print!("{}", day06::render(input(6)?)?),
+        (6, ["image", path]) => try_with!(
+            fs::write(path, day06::image(input(6)?, 10000)?),
+            "failed to write {}",
+            path
+        ),
+        (6, _) => bail!("usage: trace 6 map|image <path>"),
+        (7, [view]) => trace(7, &[view, "5", "60"])?,
+        (7, [view, workers, base_seconds]) => {
+            let (workers, base_seconds) = (parse(workers)?, parse(base_seconds)?);
+            match *view {
+                "table" => print!(
+                    "{}",
+                    day07::schedule(input(7)?, workers, base_seconds)?.table()?
+                ),
+                "gantt" => print!(
+                    "{}",
+                    day07::schedule(input(7)?, workers, base_seconds)?.gantt()?
+                ),
+                "analysis" => print!("{}", day07::analyse(input(7)?, workers, base_seconds)?),
+                _ => bail!("usage: trace 7 table|gantt|analysis [<workers> <base seconds>]"),
+            }
+        }
+        (7, _) => bail!("usage: trace 7 table|gantt|analysis [<workers> <base seconds>]"),
+        (9, [players, last_marble]) => {
+            print!("{}", day09::report(parse(players)?, parse(last_marble)?)?)
+        }
+        (9, _) => bail!("usage: trace 9 <players> <last marble>"),
+        (day, _) => bail!("no trace for day {}", day),
+    }
+    Ok(())
+}
+
+fn verify(days: &[u8]) -> Result<()> {
+    let answers = Answers::load(&answers_path())?;
+    let mut mismatches = 0;
+
+    for &day in days {
+        for (part, verdict) in answers.verify(day, &run_day(day)?).iter().enumerate() {
+            println!("day {} part {}: {}", day, part + 1, verdict);
+            if let Verdict::Mismatch { .. } = verdict {
+                mismatches += 1;
+            }
+        }
+    }
 
-    if args.len() != 1 {
-        bail!("expected exactly one argument");
+    if mismatches > 0 {
+        bail!("{} answers did not match", mismatches);
     }
 
-    run_day(try_with!(
-        args.first().unwrap().parse(),
-        "failed to parse day"
-    ))
+    Ok(())
+}
+
+fn run() -> Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["fetch", day] => fetch(parse_day(day)?),
+        ["submit", day, part] => submit(parse_day(day)?, parse(part)?),
+        ["trace", day, args @ ..] => trace(parse_day(day)?, args),
+        ["record", day] => record(parse_day(day)?),
+        ["verify"] => verify(&DAYS.collect::<Vec<_>>()),
+        ["verify", days @ ..] => verify(
+            &days
+                .iter()
+                .map(|d| parse_day(d))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        [day] => {
+            print_answer(&run_day(parse_day(day)?)?);
+            Ok(())
+        }
+        _ => bail!(
+            "usage: <day> | fetch <day> | submit <day> <part> | trace <day> <args>... | record <day> | verify [<day>...]"
+        ),
+    }
 }
 
 fn main() {
     process::exit(match run() {
-        Ok(answer) => {
-            println!("part1: {}", answer.part1);
-            println!("part2: {}", answer.part2);
-            0
-        }
+        Ok(()) => 0,
         Err(err) => {
             eprintln!("error: {}", err);
             1
@@ -153,4 +314,17 @@ mod tests {
         assert_eq!(run_day(13), Ok(Answer::new("41,22", "84,90")));
         assert_eq!(run_day(14), Ok(Answer::new("1631191756", 20219475)));
     }
+
+    #[test]
+    fn refuses_unsolvable_submissions() {
+        assert_eq!(
+            submit(10, 1).unwrap_err().to_string(),
+            "day 10 part 1 has to be solved by hand"
+        );
+        assert!(submit(10, 2).is_err());
+        assert!(submit(12, 2).is_err());
+        assert!(can_submit(12, 1));
+        assert!(can_submit(9, 2));
+        assert_eq!(submit(3, 3).unwrap_err().to_string(), "no part 3");
+    }
 }
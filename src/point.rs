@@ -1,27 +1,392 @@
-use std::ops::{Add, AddAssign};
+use crate::error::{bail, parse, require_with, Error, Result};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+// A point (or vector) on a grid where y increases downwards, as in the puzzle maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
 
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+    pub const UP: Point = Point { x: 0, y: -1 };
+    pub const DOWN: Point = Point { x: 0, y: 1 };
+    pub const LEFT: Point = Point { x: -1, y: 0 };
+    pub const RIGHT: Point = Point { x: 1, y: 0 };
+
+    // Day 6 now sums distances along each axis separately, so only the tests measure them whole.
+    #[allow(dead_code)]
+    pub fn manhattan(self, other: Point) -> i32 {
+        PointN::from(self).manhattan(PointN::from(other))
+    }
+
+    // A quarter turn anticlockwise, as seen on the map.
+    pub fn turn_left(self) -> Point {
+        Point {
+            x: self.y,
+            y: -self.x,
+        }
+    }
+
+    // A quarter turn clockwise, as seen on the map.
+    pub fn turn_right(self) -> Point {
+        Point {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+}
+
+// The arithmetic, distance and ordering are all PointN<2>'s.
 impl Add for Point {
     type Output = Point;
 
     fn add(self, other: Point) -> Point {
-        Point {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
+        (PointN::from(self) + PointN::from(other)).into()
     }
 }
 
 impl AddAssign for Point {
     fn add_assign(&mut self, other: Point) {
-        *self = Point {
-            x: self.x + other.x,
-            y: self.y + other.y,
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        (PointN::from(self) - PointN::from(other)).into()
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        (-PointN::from(self)).into()
+    }
+}
+
+impl Mul<i32> for Point {
+    type Output = Point;
+
+    fn mul(self, k: i32) -> Point {
+        (PointN::from(self) * k).into()
+    }
+}
+
+impl Ord for Point {
+    fn cmp(&self, other: &Point) -> Ordering {
+        PointN::from(*self).cmp(&PointN::from(*other))
+    }
+}
+
+impl PartialOrd for Point {
+    fn partial_cmp(&self, other: &Point) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl FromStr for Point {
+    type Err = Error;
+
+    // Parses "x,y", allowing whitespace around either coordinate.
+    fn from_str(s: &str) -> Result<Self> {
+        let [x, y] = parse_coordinates(s)?;
+        Ok(Point { x, y })
+    }
+}
+
+impl From<PointN<2>> for Point {
+    fn from(p: PointN<2>) -> Point {
+        Point { x: p[0], y: p[1] }
+    }
+}
+
+// A point (or vector) in N dimensions, for the puzzles which go beyond the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointN<const N: usize>(pub [i32; N]);
+
+impl<const N: usize> PointN<N> {
+    pub fn manhattan(self, other: PointN<N>) -> i32 {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum()
+    }
+
+    fn zip_with(self, other: PointN<N>, f: impl Fn(i32, i32) -> i32) -> PointN<N> {
+        let mut res = self;
+        for (a, b) in res.0.iter_mut().zip(other.0.iter()) {
+            *a = f(*a, *b);
+        }
+        res
+    }
+
+    fn map(self, f: impl Fn(i32) -> i32) -> PointN<N> {
+        let mut res = self;
+        for a in res.0.iter_mut() {
+            *a = f(*a);
+        }
+        res
+    }
+}
+
+impl<const N: usize> Index<usize> for PointN<N> {
+    type Output = i32;
+
+    fn index(&self, i: usize) -> &i32 {
+        &self.0[i]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for PointN<N> {
+    fn index_mut(&mut self, i: usize) -> &mut i32 {
+        &mut self.0[i]
+    }
+}
+
+impl<const N: usize> Add for PointN<N> {
+    type Output = PointN<N>;
+
+    fn add(self, other: PointN<N>) -> PointN<N> {
+        self.zip_with(other, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> AddAssign for PointN<N> {
+    fn add_assign(&mut self, other: PointN<N>) {
+        *self = *self + other;
+    }
+}
+
+impl<const N: usize> Sub for PointN<N> {
+    type Output = PointN<N>;
+
+    fn sub(self, other: PointN<N>) -> PointN<N> {
+        self.zip_with(other, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> SubAssign for PointN<N> {
+    fn sub_assign(&mut self, other: PointN<N>) {
+        *self = *self - other;
+    }
+}
+
+impl<const N: usize> Neg for PointN<N> {
+    type Output = PointN<N>;
+
+    fn neg(self) -> PointN<N> {
+        self.map(|a| -a)
+    }
+}
+
+impl<const N: usize> Mul<i32> for PointN<N> {
+    type Output = PointN<N>;
+
+    fn mul(self, k: i32) -> PointN<N> {
+        self.map(|a| a * k)
+    }
+}
+
+// The last coordinate is compared first, then the one before it and so on, which in the plane is
+// reading order: top to bottom, then left to right.
+impl<const N: usize> Ord for PointN<N> {
+    fn cmp(&self, other: &PointN<N>) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl<const N: usize> PartialOrd for PointN<N> {
+    fn partial_cmp(&self, other: &PointN<N>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> fmt::Display for PointN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, a) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", a)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> FromStr for PointN<N> {
+    type Err = Error;
+
+    // Parses N comma separated coordinates, allowing whitespace around each.
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(PointN(parse_coordinates(s)?))
+    }
+}
+
+impl From<Point> for PointN<2> {
+    fn from(p: Point) -> PointN<2> {
+        PointN([p.x, p.y])
+    }
+}
+
+// An axis-aligned box covering every point from `min` to `max` inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds<const N: usize> {
+    pub min: PointN<N>,
+    pub max: PointN<N>,
+}
+
+impl<const N: usize> Bounds<N> {
+    // The smallest box containing all the points, or None if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = PointN<N>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Bounds {
+            min: first,
+            max: first,
         };
+        for p in points {
+            bounds.extend(p);
+        }
+        Some(bounds)
+    }
+
+    pub fn extend(&mut self, p: PointN<N>) {
+        self.min = self.min.zip_with(p, i32::min);
+        self.max = self.max.zip_with(p, i32::max);
+    }
+}
+
+fn parse_coordinates<const N: usize>(s: &str) -> Result<[i32; N]> {
+    let mut coordinates = [0; N];
+    let mut parts = s.split(',');
+
+    for c in coordinates.iter_mut() {
+        *c = parse(require_with!(parts.next(), "couldn't parse point {}", s).trim())?;
+    }
+
+    if parts.next().is_some() {
+        bail!("couldn't parse point {}", s);
+    }
+
+    Ok(coordinates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let p = Point { x: 3, y: -2 };
+        let q = Point { x: -1, y: 5 };
+
+        assert_eq!(p + q - q, p);
+        assert_eq!(-p, Point { x: -3, y: 2 });
+        assert_eq!(p * 3, p + p + p);
+        assert_eq!(p.manhattan(q), 11);
+        assert_eq!(p.manhattan(Point::ORIGIN), 5);
+    }
+
+    #[test]
+    fn rotation() {
+        assert_eq!(Point::UP.turn_right(), Point::RIGHT);
+        assert_eq!(Point::RIGHT.turn_right(), Point::DOWN);
+        assert_eq!(Point::UP.turn_left(), Point::LEFT);
+        assert_eq!(Point::LEFT.turn_left(), Point::DOWN);
+    }
+
+    #[test]
+    fn reading_order() {
+        let mut points = vec![
+            Point { x: 0, y: 1 },
+            Point { x: 1, y: 0 },
+            Point { x: 0, y: 0 },
+        ];
+        points.sort();
+
+        assert_eq!(
+            points,
+            vec![
+                Point { x: 0, y: 0 },
+                Point { x: 1, y: 0 },
+                Point { x: 0, y: 1 }
+            ]
+        );
+    }
+
+    #[test]
+    fn same_order_in_any_form() {
+        let mut points = vec![
+            Point { x: 2, y: 1 },
+            Point { x: -3, y: 4 },
+            Point { x: 0, y: 1 },
+            Point { x: 5, y: -2 },
+        ];
+        let mut vectors = points.iter().map(|&p| PointN::from(p)).collect::<Vec<_>>();
+        points.sort();
+        vectors.sort();
+
+        assert_eq!(
+            vectors.into_iter().map(Point::from).collect::<Vec<_>>(),
+            points
+        );
+        assert!(PointN([9, 0, 1]) < PointN([0, 1, 1]));
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!("84, 90".parse(), Ok(Point { x: 84, y: 90 }));
+        assert_eq!("-3,7".parse(), Ok(Point { x: -3, y: 7 }));
+        assert!("1,2,3".parse::<Point>().is_err());
+        assert!("1".parse::<Point>().is_err());
+        assert_eq!(Point { x: 41, y: 22 }.to_string(), "41,22");
+    }
+
+    #[test]
+    fn n_dimensional() {
+        let p: PointN<4> = "-1,2,2,0".parse().unwrap();
+        let q: PointN<4> = "0, 0, 2, -2".parse().unwrap();
+
+        assert_eq!(p.manhattan(q), 5);
+        assert_eq!(p - q, PointN([-1, 2, 0, 2]));
+        assert_eq!(-q * 2, PointN([0, 0, -4, 4]));
+        assert_eq!(p.to_string(), "-1,2,2,0");
+        assert!("1,2".parse::<PointN<3>>().is_err());
+        assert!("1,2,3,4".parse::<PointN<3>>().is_err());
+        assert_eq!(
+            Point::from(PointN::from(Point { x: 3, y: -4 })),
+            Point { x: 3, y: -4 }
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let points: Vec<PointN<3>> = vec![PointN([0, 0, 0]), PointN([4, -1, 2]), PointN([1, 3, 1])];
+        let bounds = Bounds::from_points(points).unwrap();
+
+        assert_eq!(bounds.min, PointN([0, -1, 0]));
+        assert_eq!(bounds.max, PointN([4, 3, 2]));
+        assert_eq!(Bounds::<3>::from_points(vec![]), None);
     }
 }